@@ -0,0 +1,18 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    // Versions of netCDF found by `netcdf-sys` are made available
+    // as `#[cfg(feature = "x.y.z")]`
+    if let Ok(known_versions) = std::env::var("DEP_NETCDF_KNOWN_VERSIONS") {
+        let known_versions = known_versions.split(',').collect::<Vec<_>>();
+        println!(
+            "cargo:rustc-check-cfg=cfg(feature, values(\"{}\"))",
+            known_versions.join("\", \"")
+        );
+    }
+    for (key, _value) in std::env::vars() {
+        if let Some(version) = key.strip_prefix("DEP_NETCDF_VERSION_") {
+            println!("cargo:rustc-cfg=feature=\"{}\"", version.replace('_', "."));
+        }
+    }
+}
@@ -2,13 +2,19 @@ use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
 struct Opt {
+    /// Show only the header information, without data
+    #[structopt(short = "h")]
+    header_only: bool,
+    /// Output data only for the given variables (comma separated)
+    #[structopt(short = "v", use_delimiter = true)]
+    variables: Option<Vec<String>>,
     path: std::path::PathBuf,
 }
 
 fn main() {
     let opt = Opt::from_args();
 
-    match run(&opt.path) {
+    match run(&opt) {
         Err(e) => {
             println!("{}", e);
             std::process::exit(1);
@@ -19,140 +25,15 @@ fn main() {
     }
 }
 
-fn run(path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
-    let file = netcdf::open(path)?;
+fn run(opt: &Opt) -> Result<(), Box<dyn std::error::Error>> {
+    let file = netcdf::open(&opt.path)?;
 
-    println!("{}", file.path()?.to_str().unwrap());
-    print_file(&file)
-}
-
-fn print_file(g: &netcdf::File) -> Result<(), Box<dyn std::error::Error>> {
-    let mut dims = g.dimensions().peekable();
-    if dims.peek().is_some() {
-        println!("Dimensions:");
-        for d in dims {
-            if d.is_unlimited() {
-                println!("\t{} : Unlimited ({})", d.name(), d.len());
-            } else {
-                println!("\t{} : ({})", d.name(), d.len());
-            }
-        }
-    }
-    let mut types = g.types()?.peekable();
-    if types.peek().is_some() {
-        println!("Types:");
-        for t in types {
-            use netcdf::types::VariableType;
-            print!("\t{}: ", t.name());
-            match t {
-                VariableType::Basic(_) | VariableType::String => unreachable!(),
-                VariableType::Opaque(o) => println!("Opaque({})", o.size()),
-                VariableType::Enum(_) => println!("Enum"),
-                VariableType::Vlen(v) => println!("Vlen({})", v.typ().name()),
-                VariableType::Compound(c) => {
-                    print!("Compound({{");
-                    for field in c.fields() {
-                        print!(" {}: {} ", field.name(), field.typ().name());
-                    }
-                    println!("}})");
-                }
-            }
-        }
-    }
-    let mut variables = g.variables().peekable();
-    if variables.peek().is_some() {
-        println!("Variables:");
-        for v in variables {
-            print!("\t{}", v.name());
-            print!("(");
-            for d in v.dimensions() {
-                print!(" {} ", d.name());
-            }
-            println!("): {}", v.vartype().name());
-            for a in v.attributes() {
-                println!("\t\t{} = {:?}", a.name(), a.value()?);
-            }
-        }
-    }
-    let mut attributes = g.attributes().peekable();
-    if attributes.peek().is_some() {
-        println!("Attributes:");
-        for a in g.attributes() {
-            println!("\t\t{} = {:?}", a.name(), a.value()?);
-        }
-    }
-    if let Some(g) = g.root() {
-        for g in g.groups() {
-            println!();
-            print_group(&g)?;
-        }
-    }
-
-    Ok(())
-}
-
-fn print_group(g: &netcdf::group::Group) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Group: {}", g.name());
-
-    let mut dims = g.dimensions().peekable();
-    if dims.peek().is_some() {
-        println!("Dimensions:");
-        for d in dims {
-            if d.is_unlimited() {
-                println!("\t{} : Unlimited ({})", d.name(), d.len());
-            } else {
-                println!("\t{} : ({})", d.name(), d.len());
-            }
-        }
-    }
-    let mut types = g.types().peekable();
-    if types.peek().is_some() {
-        println!("Types:");
-        for t in types {
-            use netcdf::types::VariableType;
-            print!("\t{}: ", t.name());
-            match t {
-                VariableType::Basic(_) | VariableType::String => unreachable!(),
-                VariableType::Opaque(o) => println!("Opaque({})", o.size()),
-                VariableType::Enum(_) => println!("Enum"),
-                VariableType::Vlen(v) => println!("Vlen({})", v.typ().name()),
-                VariableType::Compound(c) => {
-                    print!("Compound({{");
-                    for field in c.fields() {
-                        print!(" {}: {} ", field.name(), field.typ().name());
-                    }
-                    println!("}})");
-                }
-            }
-        }
-    }
-
-    let mut variables = g.variables().peekable();
-    if variables.peek().is_some() {
-        println!("Variables:");
-        for v in variables {
-            print!("\t{}", v.name());
-            print!("(");
-            for d in v.dimensions() {
-                print!(" {} ", d.name());
-            }
-            println!("): {}", v.vartype().name());
-            for a in v.attributes() {
-                println!("\t\t{} = {:?}", a.name(), a.value()?);
-            }
-        }
-    }
-    let mut attributes = g.attributes().peekable();
-    if attributes.peek().is_some() {
-        println!("Attributes:");
-        for a in g.attributes() {
-            println!("\t\t{} = {:?}", a.name(), a.value()?);
-        }
-    }
-    for g in g.groups() {
-        println!();
-        print_group(&g)?;
-    }
+    let options = netcdf::cdl::CdlOptions {
+        header_only: opt.header_only,
+        variables: opt.variables.clone(),
+    };
+    let stdout = std::io::stdout();
+    file.to_cdl(std::io::BufWriter::new(stdout.lock()), &options)?;
 
     Ok(())
 }
@@ -0,0 +1,45 @@
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+struct Opt {
+    /// Name of the file to create
+    #[structopt(short = "o")]
+    output: std::path::PathBuf,
+    /// Format of the file (classic, 64-bit-offset, 64-bit-data,
+    /// netCDF-4 or netCDF-4-classic)
+    #[structopt(short = "k", default_value = "netCDF-4")]
+    kind: String,
+    /// The CDL file to read
+    path: std::path::PathBuf,
+}
+
+fn main() {
+    let opt = Opt::from_args();
+
+    match run(&opt) {
+        Err(e) => {
+            println!("{}", e);
+            std::process::exit(1);
+        }
+        Ok(()) => {
+            std::process::exit(0);
+        }
+    }
+}
+
+fn run(opt: &Opt) -> Result<(), Box<dyn std::error::Error>> {
+    let options = match opt.kind.as_str() {
+        "classic" | "nc3" | "1" => netcdf::Options::CLASSIC,
+        "64-bit-offset" | "nc6" | "2" => netcdf::Options::_64BIT_OFFSET,
+        "64-bit-data" | "nc5" | "5" => netcdf::Options::_64BIT_DATA,
+        "netCDF-4" | "nc4" | "3" => netcdf::Options::NETCDF4,
+        "netCDF-4-classic" | "nc7" | "4" => netcdf::Options::NETCDF4 | netcdf::Options::CLASSIC,
+        kind => return Err(format!("unknown kind {}", kind).into()),
+    };
+    let source = std::fs::read_to_string(&opt.path)?;
+
+    let mut file = netcdf::create_with(&opt.output, options)?;
+    netcdf::cdl::parse(&source, &mut file)?;
+
+    Ok(())
+}
@@ -0,0 +1,168 @@
+//! Derive macros for the `netcdf` crate
+//!
+//! `#[derive(NcCompound)]` implements `netcdf::types::NcCompound` for a
+//! `#[repr(C)]` struct with named fields. Fields may be basic numeric
+//! types, other structs deriving `NcCompound`, or (nested) fixed size
+//! arrays of these, which become array fields of the compound type.
+//!
+//! The name of the compound type and of the fields can be changed with
+//! `#[netcdf(rename = "name")]`.
+//!
+//! ```ignore
+//! #[derive(Copy, Clone, netcdf::NcCompound)]
+//! #[repr(C)]
+//! #[netcdf(rename = "observation")]
+//! struct Observation {
+//!     time: i64,
+//!     #[netcdf(rename = "temperature")]
+//!     temp: f32,
+//!     position: [[f64; 3]; 2],
+//! }
+//! ```
+
+#![warn(missing_docs)]
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Error, Fields, Lit, Meta, NestedMeta};
+
+/// Derive `NcCompound` (and `NcType`) for a `#[repr(C)]` struct
+#[proc_macro_derive(NcCompound, attributes(netcdf))]
+pub fn derive_nc_compound(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(
+            input.generics.span(),
+            "NcCompound can not be derived for generic types",
+        ));
+    }
+    if !has_repr_c(&input.attrs)? {
+        return Err(Error::new(
+            input.ident.span(),
+            "NcCompound requires the struct to be #[repr(C)]",
+        ));
+    }
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new(
+                    input.ident.span(),
+                    "NcCompound requires a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new(
+                input.ident.span(),
+                "NcCompound can only be derived for structs",
+            ))
+        }
+    };
+
+    let ident = &input.ident;
+    let name = rename(&input.attrs)?.unwrap_or_else(|| ident.to_string());
+
+    let mut descriptors = Vec::with_capacity(fields.len());
+    for field in fields {
+        let field_ident = field.ident.as_ref().unwrap();
+        let field_name = rename(&field.attrs)?.unwrap_or_else(|| field_ident.to_string());
+
+        let mut typ = &field.ty;
+        let mut dims = Vec::new();
+        while let syn::Type::Array(array) = typ {
+            dims.push(&array.len);
+            typ = &array.elem;
+        }
+
+        descriptors.push(quote! {
+            ::netcdf::types::FieldDescriptor {
+                name: ::std::string::String::from(#field_name),
+                offset: unsafe {
+                    ::std::ptr::addr_of!((*base).#field_ident) as usize - base as usize
+                },
+                typ: <#typ as ::netcdf::types::NcType>::type_descriptor(),
+                dims: ::std::vec![#((#dims) as usize),*],
+            }
+        });
+    }
+
+    Ok(quote! {
+        unsafe impl ::netcdf::types::NcCompound for #ident {
+            fn compound_descriptor() -> ::netcdf::types::CompoundDescriptor {
+                let uninit = ::std::mem::MaybeUninit::<Self>::uninit();
+                let base = uninit.as_ptr();
+                ::netcdf::types::CompoundDescriptor {
+                    name: ::std::string::String::from(#name),
+                    size: ::std::mem::size_of::<Self>(),
+                    fields: ::std::vec![#(#descriptors),*],
+                }
+            }
+        }
+
+        unsafe impl ::netcdf::types::NcType for #ident {
+            fn type_descriptor() -> ::netcdf::types::NcTypeDescriptor {
+                ::netcdf::types::NcTypeDescriptor::Compound(
+                    <Self as ::netcdf::types::NcCompound>::compound_descriptor(),
+                )
+            }
+        }
+    })
+}
+
+/// Whether the attributes contain `#[repr(C)]`
+fn has_repr_c(attrs: &[Attribute]) -> syn::Result<bool> {
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("repr")) {
+        if let Meta::List(list) = attr.parse_meta()? {
+            let is_c = list.nested.iter().any(|nested| match nested {
+                NestedMeta::Meta(meta) => meta.path().is_ident("C"),
+                NestedMeta::Lit(_) => false,
+            });
+            if is_c {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// The name given by `#[netcdf(rename = "name")]`, if any
+fn rename(attrs: &[Attribute]) -> syn::Result<Option<String>> {
+    let mut name = None;
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("netcdf")) {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => return Err(Error::new(meta.span(), "expected #[netcdf(...)]")),
+        };
+        for nested in &list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("rename") => {
+                    match &value.lit {
+                        Lit::Str(s) => name = Some(s.value()),
+                        lit => return Err(Error::new(lit.span(), "expected a string")),
+                    }
+                }
+                nested => {
+                    return Err(Error::new(
+                        nested.span(),
+                        "unknown attribute, expected `rename = \"...\"`",
+                    ))
+                }
+            }
+        }
+    }
+    if let Some(name) = &name {
+        if name.is_empty() {
+            return Err(Error::new(Span::call_site(), "name can not be empty"));
+        }
+    }
+    Ok(name)
+}
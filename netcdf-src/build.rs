@@ -40,6 +40,7 @@ fn main() {
     let netcdf = netcdf_config.build();
 
     println!("cargo:lib=netcdf");
+    println!("cargo:version=4.7.4");
     let search_path = format!("{}/lib", netcdf.display());
     if std::path::Path::new(&search_path).exists() {
         println!("cargo:search={}", search_path);
@@ -4,9 +4,91 @@ macro_rules! feature {
     };
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Version {
+    major: u32,
+    minor: u32,
+    patch: u32,
+}
+
+impl Version {
+    const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parses versions of the form `4.8.1` or `4.8.1-development`
+    fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let s = s.split(&['-', ' '][..]).next()?;
+        let mut parts = s.split('.').map(str::parse);
+        let major = parts.next()?.ok()?;
+        let minor = parts.next()?.ok()?;
+        let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+        Some(Self::new(major, minor, patch))
+    }
+
+    /// Reads the version from the `netcdf_meta.h` header
+    fn from_meta_header(path: &std::path::Path) -> Option<Self> {
+        let header = std::fs::read_to_string(path).ok()?;
+        let get = |name: &str| -> Option<u32> {
+            header.lines().find_map(|line| {
+                let mut words = line.split_whitespace();
+                if words.next()? != "#define" || words.next()? != name {
+                    return None;
+                }
+                words.next()?.parse().ok()
+            })
+        };
+        Some(Self::new(
+            get("NC_VERSION_MAJOR")?,
+            get("NC_VERSION_MINOR")?,
+            get("NC_VERSION_PATCH")?,
+        ))
+    }
+
+    /// Asks `nc-config` for the version, output is of the form `netCDF 4.8.1`
+    fn from_nc_config() -> Option<Self> {
+        let output = std::process::Command::new("nc-config")
+            .arg("--version")
+            .output()
+            .ok()?;
+        let output = String::from_utf8(output.stdout).ok()?;
+        Self::parse(output.trim().strip_prefix("netCDF")?)
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Versions which can be used in `#[cfg(feature = "x.y.z")]`
+const KNOWN_VERSIONS: &[Version] = &[
+    Version::new(4, 6, 0),
+    Version::new(4, 6, 1),
+    Version::new(4, 6, 2),
+    Version::new(4, 6, 3),
+    Version::new(4, 7, 0),
+    Version::new(4, 7, 1),
+    Version::new(4, 7, 2),
+    Version::new(4, 7, 3),
+    Version::new(4, 7, 4),
+    Version::new(4, 8, 0),
+    Version::new(4, 8, 1),
+    Version::new(4, 9, 0),
+    Version::new(4, 9, 1),
+    Version::new(4, 9, 2),
+];
+
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
 
+    let version;
     if feature!("STATIC").is_ok() {
         let netcdf_lib = std::env::var("DEP_NETCDFSRC_LIB").unwrap();
         let netcdf_path = std::env::var("DEP_NETCDFSRC_SEARCH").unwrap();
@@ -14,11 +96,20 @@ fn main() {
         println!("cargo:rustc-link-lib=static={}", netcdf_lib);
         println!("cargo:rustc-link-search=native={}", netcdf_path);
         println!("cargo:includedir={}/include", netcdf_path);
+
+        version = std::env::var("DEP_NETCDFSRC_VERSION")
+            .ok()
+            .and_then(|v| Version::parse(&v));
     } else {
         println!("cargo:rerun-if-env-changed=NETCDF_DIR");
         if let Ok(dir) = std::env::var("NETCDF_DIR") {
             println!("cargo:rustc-link-search={}/lib", dir);
             println!("cargo:includedir={}/include", dir);
+
+            let header = std::path::Path::new(&dir)
+                .join("include")
+                .join("netcdf_meta.h");
+            version = Version::from_meta_header(&header);
         } else if let Ok(output) = std::process::Command::new("nc-config")
             .arg("--prefix")
             .output()
@@ -28,11 +119,36 @@ fn main() {
             let dir = dir.trim();
             println!("cargo:rustc-link-search={}/lib", dir);
             println!("cargo:includedir={}/include", dir);
+
+            version = Version::from_nc_config();
         } else {
             // Link to the system netcdf
+            version = None;
         }
         println!("cargo:rustc-link-lib=netcdf");
     }
 
+    // Available versions are emitted as features, and passed on
+    // to dependents as `DEP_NETCDF_VERSION_X_Y_Z`
+    let known_versions = KNOWN_VERSIONS
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    println!(
+        "cargo:rustc-check-cfg=cfg(feature, values(\"{}\"))",
+        known_versions.join("\", \"")
+    );
+    println!("cargo:known_versions={}", known_versions.join(","));
+    if let Some(version) = version {
+        println!("cargo:version={}", version);
+        for known in KNOWN_VERSIONS.iter().filter(|&&v| v <= version) {
+            println!("cargo:rustc-cfg=feature=\"{}\"", known);
+            println!(
+                "cargo:version_{}_{}_{}=1",
+                known.major, known.minor, known.patch
+            );
+        }
+    }
+
     // Must emit: mmap, memio
 }
@@ -11,8 +11,10 @@ extern crate netcdf_src;
 
 mod netcdf_bindings;
 mod netcdf_const;
+mod netcdf_filter;
 pub use netcdf_bindings::*;
 pub use netcdf_const::*;
+pub use netcdf_filter::*;
 
 #[cfg(test)]
 mod tests {
@@ -12,6 +12,15 @@ pub struct nc_vlen_t {
     pub p: *mut c_void,
 }
 
+#[cfg(feature = "memio")]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct NC_memio {
+    pub size: usize,
+    pub memory: *mut c_void,
+    pub flags: c_int,
+}
+
 pub type nclong = c_int;
 extern "C" {
     pub static mut ncerr: c_int;
@@ -1666,4 +1675,46 @@ extern "C" {
         memory: *mut c_void,
         ncidp: *mut c_int,
     ) -> c_int;
+    #[cfg(feature = "memio")]
+    pub fn nc_create_mem(
+        path: *const c_char,
+        mode: c_int,
+        initialsize: usize,
+        ncidp: *mut c_int,
+    ) -> c_int;
+    #[cfg(feature = "memio")]
+    pub fn nc_open_memio(
+        path: *const c_char,
+        mode: c_int,
+        info: *mut NC_memio,
+        ncidp: *mut c_int,
+    ) -> c_int;
+    #[cfg(feature = "memio")]
+    pub fn nc_close_memio(ncid: c_int, info: *mut NC_memio) -> c_int;
+}
+
+#[cfg(feature = "4.8.0")]
+extern "C" {
+    pub fn nc_reclaim_data(
+        ncid: c_int,
+        xtypeid: nc_type,
+        memory: *mut c_void,
+        count: usize,
+    ) -> c_int;
+}
+
+#[cfg(feature = "4.8.1")]
+extern "C" {
+    pub fn nc_def_var_quantize(
+        ncid: c_int,
+        varid: c_int,
+        quantize_mode: c_int,
+        nsd: c_int,
+    ) -> c_int;
+    pub fn nc_inq_var_quantize(
+        ncid: c_int,
+        varid: c_int,
+        quantize_modep: *mut c_int,
+        nsdp: *mut c_int,
+    ) -> c_int;
 }
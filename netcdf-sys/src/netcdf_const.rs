@@ -21,10 +21,18 @@ pub const NC_OPAQUE: ::std::os::raw::c_int = 14;
 pub const NC_ENUM: ::std::os::raw::c_int = 15;
 pub const NC_COMPOUND: ::std::os::raw::c_int = 16;
 pub const NC_FIRSTUSERTYPEID: ::std::os::raw::c_int = 32;
+pub const NC_FILL_BYTE: ::std::os::raw::c_schar = -127;
+pub const NC_FILL_CHAR: ::std::os::raw::c_char = 0;
+pub const NC_FILL_SHORT: ::std::os::raw::c_short = -32767;
 pub const NC_FILL_INT: ::std::os::raw::c_int = -2147483647;
+pub const NC_FILL_FLOAT: f32 = 9.969_21e36;
+pub const NC_FILL_DOUBLE: f64 = 9.969_209_968_386_869e36;
 pub const NC_FILL_UBYTE: ::std::os::raw::c_int = 255;
 pub const NC_FILL_USHORT: ::std::os::raw::c_int = 65535;
 pub const NC_FILL_UINT: ::std::os::raw::c_uint = 4294967295;
+pub const NC_FILL_INT64: ::std::os::raw::c_longlong = -9223372036854775806;
+pub const NC_FILL_UINT64: ::std::os::raw::c_ulonglong = 18446744073709551614;
+pub const NC_FILL_STRING: &[u8; 1_usize] = b"\x00";
 pub const NC_MAX_BYTE: ::std::os::raw::c_int = 127;
 pub const NC_MIN_BYTE: ::std::os::raw::c_int = -128;
 pub const NC_MAX_CHAR: ::std::os::raw::c_int = 255;
@@ -72,7 +80,22 @@ pub const NC_FORMAT_PNETCDF: ::std::os::raw::c_int = 4;
 pub const NC_FORMAT_DAP2: ::std::os::raw::c_int = 5;
 pub const NC_FORMAT_DAP4: ::std::os::raw::c_int = 6;
 pub const NC_FORMAT_UNDEFINED: ::std::os::raw::c_int = 0;
+pub const NC_FORMAT_64BIT_OFFSET: ::std::os::raw::c_int = 2;
+pub const NC_FORMAT_64BIT_DATA: ::std::os::raw::c_int = 5;
+pub const NC_FORMAT_CDF5: ::std::os::raw::c_int = 5;
+pub const NC_FORMATX_NC3: ::std::os::raw::c_int = 1;
+pub const NC_FORMATX_NC_HDF5: ::std::os::raw::c_int = 2;
+pub const NC_FORMATX_NC4: ::std::os::raw::c_int = 2;
+pub const NC_FORMATX_NC_HDF4: ::std::os::raw::c_int = 3;
+pub const NC_FORMATX_PNETCDF: ::std::os::raw::c_int = 4;
+pub const NC_FORMATX_DAP2: ::std::os::raw::c_int = 5;
+pub const NC_FORMATX_DAP4: ::std::os::raw::c_int = 6;
+pub const NC_FORMATX_UDF0: ::std::os::raw::c_int = 8;
+pub const NC_FORMATX_UDF1: ::std::os::raw::c_int = 9;
+pub const NC_FORMATX_NCZARR: ::std::os::raw::c_int = 10;
+pub const NC_FORMATX_UNDEFINED: ::std::os::raw::c_int = 0;
 pub const NC_SIZEHINT_DEFAULT: ::std::os::raw::c_int = 0;
+pub const NC_MEMIO_LOCKED: ::std::os::raw::c_int = 1;
 pub const NC_UNLIMITED: ::std::os::raw::c_int = 0;
 pub const NC_GLOBAL: ::std::os::raw::c_int = -1;
 pub const NC_MAX_DIMS: ::std::os::raw::c_int = 1024;
@@ -86,10 +109,15 @@ pub const NC_ENDIAN_LITTLE: ::std::os::raw::c_int = 1;
 pub const NC_ENDIAN_BIG: ::std::os::raw::c_int = 2;
 pub const NC_CHUNKED: ::std::os::raw::c_int = 0;
 pub const NC_CONTIGUOUS: ::std::os::raw::c_int = 1;
+pub const NC_COMPACT: ::std::os::raw::c_int = 2;
 pub const NC_NOCHECKSUM: ::std::os::raw::c_int = 0;
 pub const NC_FLETCHER32: ::std::os::raw::c_int = 1;
 pub const NC_NOSHUFFLE: ::std::os::raw::c_int = 0;
 pub const NC_SHUFFLE: ::std::os::raw::c_int = 1;
+pub const NC_NOQUANTIZE: ::std::os::raw::c_int = 0;
+pub const NC_QUANTIZE_BITGROOM: ::std::os::raw::c_int = 1;
+pub const NC_QUANTIZE_GRANULARBR: ::std::os::raw::c_int = 2;
+pub const NC_QUANTIZE_BITROUND: ::std::os::raw::c_int = 3;
 pub const NC_MIN_DEFLATE_LEVEL: ::std::os::raw::c_int = 0;
 pub const NC_MAX_DEFLATE_LEVEL: ::std::os::raw::c_int = 9;
 pub const NC_NOERR: ::std::os::raw::c_int = 0;
@@ -174,6 +202,7 @@ pub const NC_ENOTBUILT: ::std::os::raw::c_int = -128;
 pub const NC_EDISKLESS: ::std::os::raw::c_int = -129;
 pub const NC_ECANTEXTEND: ::std::os::raw::c_int = -130;
 pub const NC_EMPI: ::std::os::raw::c_int = -131;
+pub const NC_ENOFILTER: ::std::os::raw::c_int = -136;
 pub const NC4_LAST_ERROR: ::std::os::raw::c_int = -131;
 pub const DIM_WITHOUT_VARIABLE: &[u8; 54_usize] =
     b"This is a netCDF dimension but not a netCDF variable.\x00";
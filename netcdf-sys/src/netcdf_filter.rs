@@ -0,0 +1,53 @@
+use std::os::raw::{c_int, c_uint};
+
+pub const H5Z_FILTER_DEFLATE: c_uint = 1;
+pub const H5Z_FILTER_SHUFFLE: c_uint = 2;
+pub const H5Z_FILTER_FLETCHER32: c_uint = 3;
+pub const H5Z_FILTER_SZIP: c_uint = 4;
+pub const H5Z_FILTER_BZIP2: c_uint = 307;
+pub const H5Z_FILTER_BLOSC: c_uint = 32001;
+pub const H5Z_FILTER_ZSTD: c_uint = 32015;
+
+pub const H5_SZIP_ALLOW_K13_OPTION_MASK: c_uint = 1;
+pub const H5_SZIP_CHIP_OPTION_MASK: c_uint = 2;
+pub const H5_SZIP_EC_OPTION_MASK: c_uint = 4;
+pub const H5_SZIP_NN_OPTION_MASK: c_uint = 32;
+
+extern "C" {
+    pub fn nc_def_var_filter(
+        ncid: c_int,
+        varid: c_int,
+        id: c_uint,
+        nparams: usize,
+        parms: *const c_uint,
+    ) -> c_int;
+    pub fn nc_inq_var_filter(
+        ncid: c_int,
+        varid: c_int,
+        idp: *mut c_uint,
+        nparams: *mut usize,
+        params: *mut c_uint,
+    ) -> c_int;
+}
+
+#[cfg(feature = "4.8.0")]
+extern "C" {
+    pub fn nc_inq_var_filter_ids(
+        ncid: c_int,
+        varid: c_int,
+        nfilters: *mut usize,
+        filterids: *mut c_uint,
+    ) -> c_int;
+    pub fn nc_inq_var_filter_info(
+        ncid: c_int,
+        varid: c_int,
+        id: c_uint,
+        nparams: *mut usize,
+        params: *mut c_uint,
+    ) -> c_int;
+}
+
+#[cfg(feature = "4.9.0")]
+extern "C" {
+    pub fn nc_inq_filter_avail(ncid: c_int, id: c_uint) -> c_int;
+}
@@ -2,6 +2,9 @@
 
 #![allow(clippy::similar_names)]
 use super::error;
+use super::file::MutableFile;
+use super::group::GroupMut;
+use super::variable::VariableMut;
 use netcdf_sys::*;
 use std::convert::TryInto;
 use std::ffi::{CStr, CString};
@@ -438,6 +441,41 @@ impl<'a> Attribute<'a> {
             x => Err(error::Error::TypeUnknown(x)),
         }
     }
+
+    /// Copy the attribute to a variable, keeping the type of the
+    /// attribute. The variable may be in another file, but
+    /// user defined types must then also exist in the other file
+    ///
+    /// # Errors
+    ///
+    /// [`error::Error::AlreadyExists`] for an attribute with
+    /// the same name on a `netCDF-4` variable, or a missing type
+    pub fn copy_attribute_to(&self, variable: &mut VariableMut) -> error::Result<()> {
+        self.copy_to(variable.ncid, variable.varid)
+    }
+
+    /// Copy the attribute to a group, see [`copy_attribute_to`](Self::copy_attribute_to)
+    pub fn copy_attribute_to_group(&self, group: &mut GroupMut) -> error::Result<()> {
+        self.copy_to(group.ncid, NC_GLOBAL)
+    }
+
+    /// Copy the attribute to the root group of a file,
+    /// see [`copy_attribute_to`](Self::copy_attribute_to)
+    pub fn copy_attribute_to_file(&self, file: &mut MutableFile) -> error::Result<()> {
+        self.copy_to(file.ncid(), NC_GLOBAL)
+    }
+
+    fn copy_to(&self, ncid: nc_type, varid: nc_type) -> error::Result<()> {
+        super::utils::checked_with_redef(ncid, || unsafe {
+            nc_copy_att(
+                self.ncid,
+                self.varid,
+                self.name.as_ptr().cast(),
+                ncid,
+                varid,
+            )
+        })
+    }
 }
 
 /// Iterator over all attributes for a location
@@ -529,6 +567,37 @@ pub enum AttrValue {
     Strs(Vec<String>),
 }
 
+impl AttrValue {
+    /// Numeric values as doubles, `None` for strings
+    #[allow(clippy::cast_precision_loss)]
+    pub(crate) fn to_f64s(&self) -> Option<Vec<f64>> {
+        let values = match self {
+            Self::Uchar(x) => vec![f64::from(*x)],
+            Self::Uchars(x) => x.iter().map(|&x| f64::from(x)).collect(),
+            Self::Schar(x) => vec![f64::from(*x)],
+            Self::Schars(x) => x.iter().map(|&x| f64::from(x)).collect(),
+            Self::Ushort(x) => vec![f64::from(*x)],
+            Self::Ushorts(x) => x.iter().map(|&x| f64::from(x)).collect(),
+            Self::Short(x) => vec![f64::from(*x)],
+            Self::Shorts(x) => x.iter().map(|&x| f64::from(x)).collect(),
+            Self::Uint(x) => vec![f64::from(*x)],
+            Self::Uints(x) => x.iter().map(|&x| f64::from(x)).collect(),
+            Self::Int(x) => vec![f64::from(*x)],
+            Self::Ints(x) => x.iter().map(|&x| f64::from(x)).collect(),
+            Self::Ulonglong(x) => vec![*x as f64],
+            Self::Ulonglongs(x) => x.iter().map(|&x| x as f64).collect(),
+            Self::Longlong(x) => vec![*x as f64],
+            Self::Longlongs(x) => x.iter().map(|&x| x as f64).collect(),
+            Self::Float(x) => vec![f64::from(*x)],
+            Self::Floats(x) => x.iter().map(|&x| f64::from(x)).collect(),
+            Self::Double(x) => vec![*x],
+            Self::Doubles(x) => x.clone(),
+            Self::Str(_) | Self::Strs(_) => return None,
+        };
+        Some(values)
+    }
+}
+
 impl<'a> Attribute<'a> {
     #[allow(clippy::needless_pass_by_value)] // All values will be small
     #[allow(clippy::too_many_lines)]
@@ -748,6 +817,43 @@ impl<'a> Attribute<'a> {
             _marker: PhantomData,
         }))
     }
+
+    pub(crate) fn rename(
+        ncid: nc_type,
+        varid: Option<nc_type>,
+        oldname: &str,
+        newname: &str,
+    ) -> error::Result<()> {
+        let oldname_c = super::utils::short_name_to_bytes(oldname)?;
+        let newname_c = super::utils::short_name_to_bytes(newname)?;
+        let result = super::utils::checked_with_redef(ncid, || unsafe {
+            nc_rename_att(
+                ncid,
+                varid.unwrap_or(NC_GLOBAL),
+                oldname_c.as_ptr().cast(),
+                newname_c.as_ptr().cast(),
+            )
+        });
+        match result {
+            Err(error::Error::Netcdf(NC_ENOTATT)) => {
+                Err(error::Error::NotFound(format!("attribute {}", oldname)))
+            }
+            result => result,
+        }
+    }
+
+    pub(crate) fn remove(ncid: nc_type, varid: Option<nc_type>, name: &str) -> error::Result<()> {
+        let cname = super::utils::short_name_to_bytes(name)?;
+        let result = super::utils::checked_with_redef(ncid, || unsafe {
+            nc_del_att(ncid, varid.unwrap_or(NC_GLOBAL), cname.as_ptr().cast())
+        });
+        match result {
+            Err(error::Error::Netcdf(NC_ENOTATT)) => {
+                Err(error::Error::NotFound(format!("attribute {}", name)))
+            }
+            result => result,
+        }
+    }
 }
 
 // Boring implementations
@@ -0,0 +1,918 @@
+//! Textual representation of files in the network Common data
+//! form Description Language (`CDL`), as printed by `ncdump`
+
+#![allow(clippy::similar_names)]
+use super::error;
+use super::file::{File, Format};
+use super::utils::{name_to_str, needs_reclaim, reclaim_values};
+use netcdf_sys::*;
+use std::convert::TryInto;
+use std::io::Write;
+
+mod parse;
+pub use parse::parse;
+
+/// Line length at which data sections are wrapped
+const MAX_LINE_LEN: usize = 80;
+
+/// Options for the `CDL` output
+#[derive(Debug, Clone, Default)]
+pub struct CdlOptions {
+    /// Only output the header, skipping the data section (`ncdump -h`)
+    pub header_only: bool,
+    /// Only output data for these variables (`ncdump -v`). Variables
+    /// in groups can be given by the full path (`group/variable`)
+    pub variables: Option<Vec<String>>,
+}
+
+/// Writes the contents of `file` as `CDL`
+pub(crate) fn write_file(
+    file: &File,
+    name: &str,
+    writer: &mut dyn Write,
+    options: &CdlOptions,
+) -> error::Result<()> {
+    let mut cdl = Cdl {
+        out: writer,
+        options,
+        nc4: matches!(file.format()?, Format::Netcdf4 | Format::Netcdf4Classic),
+    };
+    writeln!(cdl.out, "netcdf {} {{", escape_name(name))?;
+    cdl.write_group(file.ncid(), "", 0)?;
+    writeln!(cdl.out, "}}")?;
+    Ok(())
+}
+
+struct Cdl<'w> {
+    out: &'w mut dyn Write,
+    options: &'w CdlOptions,
+    nc4: bool,
+}
+
+impl<'w> Cdl<'w> {
+    fn write_group(&mut self, ncid: nc_type, path: &str, indent: usize) -> error::Result<()> {
+        let pad = " ".repeat(indent);
+
+        if self.nc4 {
+            let typeids = inq_ids(|n, ids| unsafe { nc_inq_typeids(ncid, n, ids) })?;
+            if !typeids.is_empty() {
+                writeln!(self.out, "{}types:", pad)?;
+                for xtype in typeids {
+                    self.write_type(ncid, xtype, indent + 2)?;
+                }
+            }
+        }
+
+        let dimids = inq_ids(|n, ids| unsafe { nc_inq_dimids(ncid, n, ids, false.into()) })?;
+        if !dimids.is_empty() {
+            let unlimited = if self.nc4 {
+                inq_ids(|n, ids| unsafe { nc_inq_unlimdims(ncid, n, ids) })?
+            } else {
+                let mut dimid = -1;
+                unsafe {
+                    error::checked(super::with_lock(|| nc_inq_unlimdim(ncid, &mut dimid)))?;
+                }
+                vec![dimid]
+            };
+            writeln!(self.out, "{}dimensions:", pad)?;
+            for dimid in dimids {
+                let mut name = [0_u8; NC_MAX_NAME as usize + 1];
+                let mut len = 0;
+                unsafe {
+                    error::checked(super::with_lock(|| {
+                        nc_inq_dim(ncid, dimid, name.as_mut_ptr().cast(), &mut len)
+                    }))?;
+                }
+                let name = escape_name(name_to_str(&name)?);
+                if unlimited.contains(&dimid) {
+                    writeln!(
+                        self.out,
+                        "{}\t{} = UNLIMITED ; // ({} currently)",
+                        pad, name, len
+                    )?;
+                } else {
+                    writeln!(self.out, "{}\t{} = {} ;", pad, name, len)?;
+                }
+            }
+        }
+
+        let varids = inq_ids(|n, ids| unsafe { nc_inq_varids(ncid, n, ids) })?;
+        if !varids.is_empty() {
+            writeln!(self.out, "{}variables:", pad)?;
+            for &varid in &varids {
+                let (name, xtype, dimids) = inq_var(ncid, varid)?;
+                write!(
+                    self.out,
+                    "{}\t{} {}",
+                    pad,
+                    type_name(ncid, xtype)?,
+                    escape_name(&name)
+                )?;
+                if !dimids.is_empty() {
+                    let names = dimids
+                        .iter()
+                        .map(|&dimid| {
+                            let mut name = [0_u8; NC_MAX_NAME as usize + 1];
+                            unsafe {
+                                error::checked(super::with_lock(|| {
+                                    nc_inq_dimname(ncid, dimid, name.as_mut_ptr().cast())
+                                }))?;
+                            }
+                            Ok(escape_name(name_to_str(&name)?))
+                        })
+                        .collect::<error::Result<Vec<_>>>()?;
+                    write!(self.out, "({})", names.join(", "))?;
+                }
+                writeln!(self.out, " ;")?;
+                self.write_attributes(ncid, varid, &name, indent)?;
+            }
+        }
+
+        let mut natts = 0;
+        unsafe {
+            error::checked(super::with_lock(|| nc_inq_natts(ncid, &mut natts)))?;
+        }
+        if natts > 0 {
+            if path.is_empty() {
+                writeln!(self.out, "\n{}// global attributes:", pad)?;
+            } else {
+                writeln!(self.out, "\n{}// group attributes:", pad)?;
+            }
+            self.write_attributes(ncid, NC_GLOBAL, "", indent)?;
+        }
+
+        if !self.options.header_only && !varids.is_empty() {
+            writeln!(self.out, "{}data:", pad)?;
+            for varid in varids {
+                let (name, _, _) = inq_var(ncid, varid)?;
+                if self.is_selected(path, &name) {
+                    self.write_data(ncid, varid, &name, indent)?;
+                }
+            }
+        }
+
+        if self.nc4 {
+            let groups = inq_ids(|n, ids| unsafe { nc_inq_grps(ncid, n, ids) })?;
+            for group in groups {
+                let mut name = [0_u8; NC_MAX_NAME as usize + 1];
+                unsafe {
+                    error::checked(super::with_lock(|| {
+                        nc_inq_grpname(group, name.as_mut_ptr().cast())
+                    }))?;
+                }
+                let name = name_to_str(&name)?;
+                writeln!(self.out, "\n{}group: {} {{", pad, escape_name(name))?;
+                self.write_group(group, &format!("{}/{}", path, name), indent + 2)?;
+                writeln!(self.out, "{}  }} // group {}", pad, escape_name(name))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn is_selected(&self, path: &str, name: &str) -> bool {
+        match &self.options.variables {
+            None => true,
+            Some(variables) => {
+                let full = format!("{}/{}", path, name);
+                variables
+                    .iter()
+                    .any(|v| v == name || v == &full || v == &full[1..])
+            }
+        }
+    }
+
+    fn write_type(&mut self, ncid: nc_type, xtype: nc_type, indent: usize) -> error::Result<()> {
+        let pad = " ".repeat(indent);
+        let mut name = [0_u8; NC_MAX_NAME as usize + 1];
+        let mut size = 0;
+        let mut base = 0;
+        let mut nfields = 0;
+        let mut class = 0;
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_inq_user_type(
+                    ncid,
+                    xtype,
+                    name.as_mut_ptr().cast(),
+                    &mut size,
+                    &mut base,
+                    &mut nfields,
+                    &mut class,
+                )
+            }))?;
+        }
+        let name = escape_name(name_to_str(&name)?);
+
+        match class {
+            NC_OPAQUE => writeln!(self.out, "{}opaque({}) {} ;", pad, size, name)?,
+            NC_VLEN => writeln!(self.out, "{}{}(*) {} ;", pad, type_name(ncid, base)?, name)?,
+            NC_ENUM => {
+                let members = match describe(ncid, xtype)? {
+                    Type::Enum { members, .. } => members,
+                    _ => return Err(error::Error::TypeUnknown(xtype)),
+                };
+                let members = members
+                    .iter()
+                    .map(|(member, value)| format!("{} = {}", escape_name(member), value))
+                    .collect::<Vec<_>>();
+                writeln!(
+                    self.out,
+                    "{}{} enum {} {{{}}} ;",
+                    pad,
+                    type_name(ncid, base)?,
+                    name,
+                    members.join(", ")
+                )?;
+            }
+            NC_COMPOUND => {
+                writeln!(self.out, "{}compound {} {{", pad, name)?;
+                let fields = match describe(ncid, xtype)? {
+                    Type::Compound { fields, .. } => fields,
+                    _ => return Err(error::Error::TypeUnknown(xtype)),
+                };
+                for field in fields {
+                    write!(
+                        self.out,
+                        "{}  {} {}",
+                        pad,
+                        type_name(ncid, field.xtype)?,
+                        escape_name(&field.name)
+                    )?;
+                    if !field.dims.is_empty() {
+                        let dims = field
+                            .dims
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>();
+                        write!(self.out, "({})", dims.join(", "))?;
+                    }
+                    writeln!(self.out, " ;")?;
+                }
+                writeln!(self.out, "{}}}; // {}", pad, name)?;
+            }
+            _ => return Err(error::Error::TypeUnknown(xtype)),
+        }
+        Ok(())
+    }
+
+    fn write_attributes(
+        &mut self,
+        ncid: nc_type,
+        varid: nc_type,
+        varname: &str,
+        indent: usize,
+    ) -> error::Result<()> {
+        let pad = " ".repeat(indent);
+        let mut natts = 0;
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_inq_varnatts(ncid, varid, &mut natts)
+            }))?;
+        }
+        for attnum in 0..natts {
+            let mut name = [0_u8; NC_MAX_NAME as usize + 1];
+            let mut xtype = 0;
+            let mut len = 0;
+            unsafe {
+                error::checked(super::with_lock(|| {
+                    nc_inq_attname(ncid, varid, attnum, name.as_mut_ptr().cast())
+                }))?;
+                error::checked(super::with_lock(|| {
+                    nc_inq_att(ncid, varid, name.as_ptr().cast(), &mut xtype, &mut len)
+                }))?;
+            }
+
+            let mut size = 0;
+            unsafe {
+                error::checked(super::with_lock(|| {
+                    nc_inq_type(ncid, xtype, std::ptr::null_mut(), &mut size)
+                }))?;
+            }
+            // u64 ensures the buffer is aligned for all types
+            let mut buffer = vec![0_u64; len * size / 8 + 1];
+            unsafe {
+                error::checked(super::with_lock(|| {
+                    nc_get_att(
+                        ncid,
+                        varid,
+                        name.as_ptr().cast(),
+                        buffer.as_mut_ptr().cast(),
+                    )
+                }))?;
+            }
+            let values =
+                unsafe { std::slice::from_raw_parts(buffer.as_ptr().cast::<u8>(), len * size) };
+
+            let typ = describe(ncid, xtype);
+            let text = typ.map(|typ| {
+                if xtype == NC_CHAR {
+                    format_char_attribute(values, &pad)
+                } else {
+                    values
+                        .chunks(size)
+                        .map(|value| format_value(&typ, value, true))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                }
+            });
+            if needs_reclaim(ncid, xtype)? {
+                reclaim_values(ncid, xtype, buffer.as_mut_ptr().cast(), len)?;
+            }
+            let text = text?;
+
+            write!(self.out, "{}\t\t", pad)?;
+            if xtype == NC_STRING || xtype > NC_MAX_ATOMIC_TYPE {
+                write!(self.out, "{} ", type_name(ncid, xtype)?)?;
+            }
+            writeln!(
+                self.out,
+                "{}:{} = {} ;",
+                escape_name(varname),
+                escape_name(name_to_str(&name)?),
+                text
+            )?;
+        }
+        Ok(())
+    }
+
+    fn write_data(
+        &mut self,
+        ncid: nc_type,
+        varid: nc_type,
+        name: &str,
+        indent: usize,
+    ) -> error::Result<()> {
+        let (_, xtype, dimids) = inq_var(ncid, varid)?;
+        let shape = dimids
+            .iter()
+            .map(|&dimid| {
+                let mut len = 0;
+                unsafe {
+                    error::checked(super::with_lock(|| nc_inq_dimlen(ncid, dimid, &mut len)))?;
+                }
+                Ok(len)
+            })
+            .collect::<error::Result<Vec<usize>>>()?;
+        if shape.contains(&0) {
+            return Ok(());
+        }
+
+        let typ = describe(ncid, xtype)?;
+        let mut size = 0;
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_inq_type(ncid, xtype, std::ptr::null_mut(), &mut size)
+            }))?;
+        }
+        // Values equal to the fill value are written as `_`
+        let fill = if xtype == NC_CHAR || xtype == NC_STRING || xtype > NC_MAX_ATOMIC_TYPE {
+            None
+        } else {
+            let mut fill = 0_u64;
+            unsafe {
+                error::checked(super::with_lock(|| {
+                    nc_inq_var_fill(
+                        ncid,
+                        varid,
+                        std::ptr::null_mut(),
+                        std::ptr::addr_of_mut!(fill).cast(),
+                    )
+                }))?;
+            }
+            Some(fill.to_ne_bytes())
+        };
+
+        let name = escape_name(name);
+        let rank = shape.len();
+        let mut line = Line {
+            out: &mut *self.out,
+            col: 0,
+            indent: 0,
+        };
+        write!(line.out, "\n{} {}", " ".repeat(indent), name)?;
+        let rowlen = if rank == 0 { 1 } else { shape[rank - 1] };
+        if rank <= 1 {
+            write!(line.out, " = ")?;
+            line.indent = indent + name.len() + 4;
+        } else {
+            write!(line.out, " =\n{}  ", " ".repeat(indent))?;
+            line.indent = indent + 2;
+        }
+        line.col = line.indent;
+
+        let nrows = shape.iter().take(rank.saturating_sub(1)).product::<usize>();
+        let mut start = vec![0; rank];
+        let mut count = vec![1; rank];
+        if rank > 0 {
+            count[rank - 1] = rowlen;
+        }
+        let mut buffer = vec![0_u64; rowlen * size / 8 + 1];
+        let reclaim = needs_reclaim(ncid, xtype)?;
+        for row in 0..nrows {
+            unsafe {
+                error::checked(super::with_lock(|| {
+                    nc_get_vara(
+                        ncid,
+                        varid,
+                        start.as_ptr(),
+                        count.as_ptr(),
+                        buffer.as_mut_ptr().cast(),
+                    )
+                }))?;
+            }
+            let values =
+                unsafe { std::slice::from_raw_parts(buffer.as_ptr().cast::<u8>(), rowlen * size) };
+            let tokens = if xtype == NC_CHAR {
+                vec![quote(trim_nul(values))]
+            } else {
+                values
+                    .chunks(size)
+                    .map(|value| match fill {
+                        Some(fill) if value == &fill[..size] => "_".to_string(),
+                        _ => format_value(&typ, value, false),
+                    })
+                    .collect()
+            };
+            if reclaim {
+                reclaim_values(ncid, xtype, buffer.as_mut_ptr().cast(), rowlen)?;
+            }
+
+            let last_row = row + 1 == nrows;
+            let ntokens = tokens.len();
+            for (i, token) in tokens.into_iter().enumerate() {
+                let token = if i + 1 == ntokens && last_row {
+                    format!("{} ;", token)
+                } else {
+                    format!("{},", token)
+                };
+                line.put(&token, i > 0)?;
+            }
+            if !last_row {
+                line.newline()?;
+            }
+
+            // Advance to the next row, with the last dimension varying fastest
+            for dim in (0..rank.saturating_sub(1)).rev() {
+                start[dim] += 1;
+                if start[dim] < shape[dim] {
+                    break;
+                }
+                start[dim] = 0;
+            }
+        }
+        writeln!(line.out)?;
+        Ok(())
+    }
+}
+
+/// Output of data values, wrapped at `MAX_LINE_LEN`
+struct Line<'a> {
+    out: &'a mut dyn Write,
+    col: usize,
+    indent: usize,
+}
+
+impl<'a> Line<'a> {
+    /// Writes `token`, preceded by a space unless
+    /// at the start of a line
+    fn put(&mut self, token: &str, space: bool) -> error::Result<()> {
+        let space = usize::from(space);
+        if self.col + space + token.len() > MAX_LINE_LEN && self.col > self.indent {
+            self.newline()?;
+        } else if space > 0 {
+            write!(self.out, " ")?;
+            self.col += 1;
+        }
+        write!(self.out, "{}", token)?;
+        self.col += token.len();
+        Ok(())
+    }
+
+    fn newline(&mut self) -> error::Result<()> {
+        write!(self.out, "\n{}", " ".repeat(self.indent))?;
+        self.col = self.indent;
+        Ok(())
+    }
+}
+
+/// Layout of a type, used to interpret values read from the file
+enum Type {
+    Atomic(nc_type),
+    Enum {
+        base: nc_type,
+        members: Vec<(String, i64)>,
+    },
+    Opaque {
+        size: usize,
+    },
+    Vlen {
+        base: Box<Type>,
+        size: usize,
+    },
+    Compound {
+        fields: Vec<Field>,
+        size: usize,
+    },
+}
+
+struct Field {
+    name: String,
+    offset: usize,
+    xtype: nc_type,
+    typ: Type,
+    size: usize,
+    dims: Vec<usize>,
+}
+
+fn describe(ncid: nc_type, xtype: nc_type) -> error::Result<Type> {
+    if xtype <= NC_MAX_ATOMIC_TYPE {
+        return Ok(Type::Atomic(xtype));
+    }
+    let mut size = 0;
+    let mut base = 0;
+    let mut nfields = 0;
+    let mut class = 0;
+    unsafe {
+        error::checked(super::with_lock(|| {
+            nc_inq_user_type(
+                ncid,
+                xtype,
+                std::ptr::null_mut(),
+                &mut size,
+                &mut base,
+                &mut nfields,
+                &mut class,
+            )
+        }))?;
+    }
+    match class {
+        NC_OPAQUE => Ok(Type::Opaque { size }),
+        NC_VLEN => {
+            let mut base_size = 0;
+            unsafe {
+                error::checked(super::with_lock(|| {
+                    nc_inq_type(ncid, base, std::ptr::null_mut(), &mut base_size)
+                }))?;
+            }
+            Ok(Type::Vlen {
+                base: Box::new(describe(ncid, base)?),
+                size: base_size,
+            })
+        }
+        NC_ENUM => {
+            let members = (0..nfields.try_into()?)
+                .map(|idx| {
+                    let mut name = [0_u8; NC_MAX_NAME as usize + 1];
+                    let mut value = [0_u8; 8];
+                    unsafe {
+                        error::checked(super::with_lock(|| {
+                            nc_inq_enum_member(
+                                ncid,
+                                xtype,
+                                idx,
+                                name.as_mut_ptr().cast(),
+                                value.as_mut_ptr().cast(),
+                            )
+                        }))?;
+                    }
+                    Ok((name_to_str(&name)?.to_string(), integer(base, &value)))
+                })
+                .collect::<error::Result<Vec<_>>>()?;
+            Ok(Type::Enum { base, members })
+        }
+        NC_COMPOUND => {
+            let fields = (0..nfields.try_into()?)
+                .map(|fieldid| {
+                    let mut name = [0_u8; NC_MAX_NAME as usize + 1];
+                    let mut offset = 0;
+                    let mut fieldtype = 0;
+                    let mut ndims = 0;
+                    unsafe {
+                        error::checked(super::with_lock(|| {
+                            nc_inq_compound_field(
+                                ncid,
+                                xtype,
+                                fieldid,
+                                name.as_mut_ptr().cast(),
+                                &mut offset,
+                                &mut fieldtype,
+                                &mut ndims,
+                                std::ptr::null_mut(),
+                            )
+                        }))?;
+                    }
+                    let mut dims = vec![0; ndims.try_into()?];
+                    if ndims > 0 {
+                        unsafe {
+                            error::checked(super::with_lock(|| {
+                                nc_inq_compound_fielddim_sizes(
+                                    ncid,
+                                    xtype,
+                                    fieldid,
+                                    dims.as_mut_ptr(),
+                                )
+                            }))?;
+                        }
+                    }
+                    let mut size = 0;
+                    unsafe {
+                        error::checked(super::with_lock(|| {
+                            nc_inq_type(ncid, fieldtype, std::ptr::null_mut(), &mut size)
+                        }))?;
+                    }
+                    Ok(Field {
+                        name: name_to_str(&name)?.to_string(),
+                        offset,
+                        xtype: fieldtype,
+                        typ: describe(ncid, fieldtype)?,
+                        size,
+                        dims: dims
+                            .into_iter()
+                            .map(TryInto::try_into)
+                            .collect::<Result<_, _>>()?,
+                    })
+                })
+                .collect::<error::Result<Vec<_>>>()?;
+            Ok(Type::Compound { fields, size })
+        }
+        _ => Err(error::Error::TypeUnknown(xtype)),
+    }
+}
+
+fn inq_var(ncid: nc_type, varid: nc_type) -> error::Result<(String, nc_type, Vec<nc_type>)> {
+    let mut name = [0_u8; NC_MAX_NAME as usize + 1];
+    let mut xtype = 0;
+    let mut ndims = 0;
+    unsafe {
+        error::checked(super::with_lock(|| {
+            nc_inq_var(
+                ncid,
+                varid,
+                name.as_mut_ptr().cast(),
+                &mut xtype,
+                &mut ndims,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            )
+        }))?;
+    }
+    let mut dimids = vec![0; ndims.try_into()?];
+    unsafe {
+        error::checked(super::with_lock(|| {
+            nc_inq_vardimid(ncid, varid, dimids.as_mut_ptr())
+        }))?;
+    }
+    Ok((name_to_str(&name)?.to_string(), xtype, dimids))
+}
+
+/// Calls `f` first to get the number of identifiers, then to get the identifiers
+fn inq_ids<F>(f: F) -> error::Result<Vec<nc_type>>
+where
+    F: Fn(*mut nc_type, *mut nc_type) -> nc_type,
+{
+    let mut n = 0;
+    error::checked(super::with_lock(|| f(&mut n, std::ptr::null_mut())))?;
+    let mut ids = vec![0; n.try_into()?];
+    if n > 0 {
+        error::checked(super::with_lock(|| {
+            f(std::ptr::null_mut(), ids.as_mut_ptr())
+        }))?;
+    }
+    Ok(ids)
+}
+
+fn type_name(ncid: nc_type, xtype: nc_type) -> error::Result<String> {
+    let name = match xtype {
+        NC_BYTE => "byte",
+        NC_CHAR => "char",
+        NC_SHORT => "short",
+        NC_INT => "int",
+        NC_FLOAT => "float",
+        NC_DOUBLE => "double",
+        NC_UBYTE => "ubyte",
+        NC_USHORT => "ushort",
+        NC_UINT => "uint",
+        NC_INT64 => "int64",
+        NC_UINT64 => "uint64",
+        NC_STRING => "string",
+        _ => {
+            let mut name = [0_u8; NC_MAX_NAME as usize + 1];
+            unsafe {
+                error::checked(super::with_lock(|| {
+                    nc_inq_type(ncid, xtype, name.as_mut_ptr().cast(), std::ptr::null_mut())
+                }))?;
+            }
+            return Ok(escape_name(name_to_str(&name)?));
+        }
+    };
+    Ok(name.to_string())
+}
+
+/// Reads an integer of type `xtype` from the start of `bytes`
+fn integer(xtype: nc_type, bytes: &[u8]) -> i64 {
+    match xtype {
+        NC_BYTE => i8::from_ne_bytes([bytes[0]]).into(),
+        NC_UBYTE => u8::from_ne_bytes([bytes[0]]).into(),
+        NC_SHORT => i16::from_ne_bytes([bytes[0], bytes[1]]).into(),
+        NC_USHORT => u16::from_ne_bytes([bytes[0], bytes[1]]).into(),
+        NC_INT => i32::from_ne_bytes(bytes[..4].try_into().unwrap()).into(),
+        NC_UINT => u32::from_ne_bytes(bytes[..4].try_into().unwrap()).into(),
+        NC_INT64 => i64::from_ne_bytes(bytes[..8].try_into().unwrap()),
+        #[allow(clippy::cast_possible_wrap)]
+        NC_UINT64 => u64::from_ne_bytes(bytes[..8].try_into().unwrap()) as i64,
+        _ => 0,
+    }
+}
+
+/// Formats a single value, `attribute` adds the type suffixes
+/// used for attributes
+fn format_value(typ: &Type, bytes: &[u8], attribute: bool) -> String {
+    let suffix = |s: &'static str| if attribute { s } else { "" };
+    match typ {
+        Type::Atomic(xtype) => match *xtype {
+            NC_BYTE => format!("{}{}", integer(NC_BYTE, bytes), suffix("b")),
+            NC_CHAR => quote(trim_nul(&bytes[..1])),
+            NC_SHORT => format!("{}{}", integer(NC_SHORT, bytes), suffix("s")),
+            NC_INT => integer(NC_INT, bytes).to_string(),
+            NC_FLOAT => {
+                let value = f32::from_ne_bytes(bytes[..4].try_into().unwrap());
+                format!("{}{}", format_g(value.into(), 7, attribute), suffix("f"))
+            }
+            NC_DOUBLE => {
+                let value = f64::from_ne_bytes(bytes[..8].try_into().unwrap());
+                format_g(value, 15, attribute)
+            }
+            NC_UBYTE => format!("{}{}", integer(NC_UBYTE, bytes), suffix("UB")),
+            NC_USHORT => format!("{}{}", integer(NC_USHORT, bytes), suffix("US")),
+            NC_UINT => format!("{}{}", integer(NC_UINT, bytes), suffix("U")),
+            NC_INT64 => format!("{}{}", integer(NC_INT64, bytes), suffix("L")),
+            NC_UINT64 => {
+                let value = u64::from_ne_bytes(bytes[..8].try_into().unwrap());
+                format!("{}{}", value, suffix("UL"))
+            }
+            NC_STRING => {
+                let ptr =
+                    usize::from_ne_bytes(bytes[..std::mem::size_of::<usize>()].try_into().unwrap())
+                        as *const std::os::raw::c_char;
+                if ptr.is_null() {
+                    "NIL".to_string()
+                } else {
+                    quote(unsafe { std::ffi::CStr::from_ptr(ptr) }.to_bytes())
+                }
+            }
+            _ => "?".to_string(),
+        },
+        Type::Enum { base, members } => {
+            let value = integer(*base, bytes);
+            members
+                .iter()
+                .find(|(_, v)| *v == value)
+                .map_or_else(|| value.to_string(), |(name, _)| escape_name(name))
+        }
+        Type::Opaque { size } => {
+            let mut s = "0X".to_string();
+            for b in &bytes[..*size] {
+                s.push_str(&format!("{:02X}", b));
+            }
+            s
+        }
+        Type::Vlen { base, size } => {
+            let vlen = unsafe { std::ptr::read_unaligned(bytes.as_ptr().cast::<nc_vlen_t>()) };
+            let values = if vlen.len == 0 || vlen.p.is_null() {
+                &[][..]
+            } else {
+                unsafe { std::slice::from_raw_parts(vlen.p.cast::<u8>(), vlen.len * size) }
+            };
+            let values = values
+                .chunks(*size)
+                .map(|value| format_value(base, value, attribute))
+                .collect::<Vec<_>>();
+            format!("{{{}}}", values.join(", "))
+        }
+        Type::Compound { fields, size } => {
+            let bytes = &bytes[..*size];
+            let fields = fields
+                .iter()
+                .map(|field| {
+                    let nelems = field.dims.iter().product::<usize>();
+                    let bytes = &bytes[field.offset..field.offset + nelems * field.size];
+                    if field.xtype == NC_CHAR && !field.dims.is_empty() {
+                        quote(trim_nul(bytes))
+                    } else if field.dims.is_empty() {
+                        format_value(&field.typ, bytes, attribute)
+                    } else {
+                        let values = bytes
+                            .chunks(field.size)
+                            .map(|value| format_value(&field.typ, value, attribute))
+                            .collect::<Vec<_>>();
+                        format!("{{{}}}", values.join(", "))
+                    }
+                })
+                .collect::<Vec<_>>();
+            format!("{{{}}}", fields.join(", "))
+        }
+    }
+}
+
+/// Text attributes are broken into several lines after newlines
+fn format_char_attribute(bytes: &[u8], pad: &str) -> String {
+    let bytes = trim_nul(bytes);
+    let mut lines = Vec::new();
+    let mut rest = bytes;
+    while let Some(pos) = rest.iter().position(|&b| b == b'\n') {
+        if pos + 1 == rest.len() {
+            break;
+        }
+        lines.push(quote(&rest[..=pos]));
+        rest = &rest[pos + 1..];
+    }
+    lines.push(quote(rest));
+    lines.join(&format!(",\n{}\t\t\t", pad))
+}
+
+fn trim_nul(bytes: &[u8]) -> &[u8] {
+    let len = bytes.iter().rposition(|&b| b != 0).map_or(0, |pos| pos + 1);
+    &bytes[..len]
+}
+
+/// Quotes and escapes a string
+fn quote(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(bytes.len() + 2);
+    s.push('"');
+    for c in String::from_utf8_lossy(bytes).chars() {
+        match c {
+            '\u{8}' => s.push_str("\\b"),
+            '\u{c}' => s.push_str("\\f"),
+            '\n' => s.push_str("\\n"),
+            '\r' => s.push_str("\\r"),
+            '\t' => s.push_str("\\t"),
+            '\u{b}' => s.push_str("\\v"),
+            '\\' => s.push_str("\\\\"),
+            '\'' => s.push_str("\\'"),
+            '"' => s.push_str("\\\""),
+            c if (c as u32) < 0x20 || c as u32 == 0x7f => {
+                s.push_str(&format!("\\{:03o}", c as u32));
+            }
+            c => s.push(c),
+        }
+    }
+    s.push('"');
+    s
+}
+
+/// Escapes characters with special meaning in `CDL` names
+pub(crate) fn escape_name(name: &str) -> String {
+    let mut s = String::with_capacity(name.len());
+    for (i, c) in name.chars().enumerate() {
+        if " !\"#$%&'()*,:;<=>?[\\]^`{|}".contains(c) || (i == 0 && c.is_ascii_digit()) {
+            s.push('\\');
+        }
+        s.push(c);
+    }
+    s
+}
+
+/// Formats `value` like `printf("%.{precision}g")`, or
+/// like `ncdump` does for attributes when `keep_point` is set,
+/// keeping the decimal point but removing trailing zeros
+#[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+pub(crate) fn format_g(value: f64, precision: usize, keep_point: bool) -> String {
+    if value.is_nan() {
+        return "NaN".to_string();
+    }
+    if value.is_infinite() {
+        return if value > 0.0 { "Infinity" } else { "-Infinity" }.to_string();
+    }
+
+    let precision = precision.max(1);
+    let scientific = format!("{:.*e}", precision - 1, value);
+    let mut parts = scientific.splitn(2, 'e');
+    let mantissa = parts.next().unwrap();
+    let exponent: i32 = parts.next().unwrap().parse().unwrap();
+
+    let trim = |mut s: String| {
+        if s.contains('.') {
+            while s.ends_with('0') {
+                s.pop();
+            }
+            if !keep_point && s.ends_with('.') {
+                s.pop();
+            }
+        } else if keep_point {
+            s.push('.');
+        }
+        s
+    };
+    if exponent < -4 || exponent >= precision as i32 {
+        format!(
+            "{}e{}{:02}",
+            trim(mantissa.to_string()),
+            if exponent < 0 { '-' } else { '+' },
+            exponent.abs()
+        )
+    } else {
+        let decimals = (precision as i32 - 1 - exponent) as usize;
+        trim(format!("{:.*}", decimals, value))
+    }
+}
@@ -0,0 +1,1311 @@
+//! Parsing of `CDL` into a file, as done by `ncgen`
+
+use super::super::error;
+use super::super::file::MutableFile;
+use super::super::utils::{enddef, redef};
+use super::{describe, Type};
+use netcdf_sys::*;
+use std::convert::TryInto;
+use std::ffi::CString;
+
+/// Define the contents of `CDL` source in `file`
+///
+/// Types, dimensions, variables, attributes, and groups are
+/// created as declared, and the values of the `data:` sections
+/// are written to the variables. The `netcdf` name of the source
+/// is not used, the format is given by how `file` was created.
+///
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut file = netcdf::create("simple.nc")?;
+/// netcdf::cdl::parse(
+///     "netcdf simple {
+///     dimensions:
+///         x = 3 ;
+///     variables:
+///         int x(x) ;
+///             x:units = \"m\" ;
+///     data:
+///         x = 1, 2, 3 ;
+///     }",
+///     &mut file,
+/// )?;
+/// # Ok(()) }
+/// ```
+///
+/// # Errors
+///
+/// Syntax errors in `source`, with the offending line, or errors
+/// from the netcdf layer when defining the contents
+pub fn parse(source: &str, file: &mut MutableFile) -> error::Result<()> {
+    let mut parser = Parser {
+        tokens: lex(source)?,
+        pos: 0,
+        unlimited: Vec::new(),
+    };
+    let ncid = file.ncid();
+
+    parser.expect_name("netcdf")?;
+    parser.name()?;
+    parser.expect('{')?;
+    redef(ncid)?;
+    let result = parser.group(ncid);
+    enddef(ncid)?;
+    result?;
+    parser.expect('}')?;
+    match parser.peek() {
+        Token::Eof => Ok(()),
+        _ => Err(parser.error("end of input")),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    /// Section keyword, such as `variables:`
+    Section(String),
+    Number(String),
+    Str(Vec<u8>),
+    Punct(char),
+    Eof,
+}
+
+fn lex(source: &str) -> error::Result<Vec<(Token, usize)>> {
+    let chars = source.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    let err = |line: usize, msg: &str| error::Error::Str(format!("line {}: {}", line, msg));
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        if c == '\n' {
+            line += 1;
+            i += 1;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && next == Some('/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '"' {
+            // Escapes can give bytes which are not valid utf-8
+            let mut s = Vec::new();
+            i += 1;
+            loop {
+                let c = *chars
+                    .get(i)
+                    .ok_or_else(|| err(line, "unterminated string"))?;
+                i += 1;
+                match c {
+                    '"' => break,
+                    '\\' => {
+                        let c = *chars
+                            .get(i)
+                            .ok_or_else(|| err(line, "unterminated string"))?;
+                        i += 1;
+                        match c {
+                            'a' => s.push(0x07),
+                            'b' => s.push(0x08),
+                            'f' => s.push(0x0c),
+                            'n' => s.push(b'\n'),
+                            'r' => s.push(b'\r'),
+                            't' => s.push(b'\t'),
+                            'v' => s.push(0x0b),
+                            'x' => {
+                                let start = i;
+                                while i < chars.len()
+                                    && i < start + 2
+                                    && chars[i].is_ascii_hexdigit()
+                                {
+                                    i += 1;
+                                }
+                                let digits = chars[start..i].iter().collect::<String>();
+                                let value = u8::from_str_radix(&digits, 16)
+                                    .map_err(|_| err(line, "invalid escape"))?;
+                                s.push(value);
+                            }
+                            '0'..='7' => {
+                                let start = i - 1;
+                                while i < chars.len()
+                                    && i < start + 3
+                                    && ('0'..='7').contains(&chars[i])
+                                {
+                                    i += 1;
+                                }
+                                let digits = chars[start..i].iter().collect::<String>();
+                                let value = u8::from_str_radix(&digits, 8)
+                                    .map_err(|_| err(line, "invalid escape"))?;
+                                s.push(value);
+                            }
+                            c => push_char(&mut s, c),
+                        }
+                    }
+                    '\n' => {
+                        line += 1;
+                        s.push(b'\n');
+                    }
+                    c => push_char(&mut s, c),
+                }
+            }
+            tokens.push((Token::Str(s), line));
+        } else if "{}()=,;:*".contains(c) {
+            tokens.push((Token::Punct(c), line));
+            i += 1;
+        } else if c.is_ascii_digit()
+            || (c == '.' && matches!(next, Some(n) if n.is_ascii_digit()))
+            || ((c == '-' || c == '+')
+                && matches!(next, Some(n) if n.is_ascii_digit() || ".IN".contains(n)))
+        {
+            let start = i;
+            i += 1;
+            while i < chars.len() {
+                let c = chars[i];
+                let exponent_sign = (c == '-' || c == '+')
+                    && "eE".contains(chars[i - 1])
+                    && !is_hex(&chars[start..i]);
+                if c.is_ascii_alphanumeric() || c == '.' || exponent_sign {
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            tokens.push((Token::Number(chars[start..i].iter().collect()), line));
+        } else if c.is_alphabetic() || c == '_' || c == '\\' || !c.is_ascii() {
+            let mut name = String::new();
+            while i < chars.len() {
+                let c = chars[i];
+                if c == '\\' {
+                    let escaped = *chars
+                        .get(i + 1)
+                        .ok_or_else(|| err(line, "invalid escape"))?;
+                    name.push(escaped);
+                    i += 2;
+                } else if c.is_alphanumeric() || "_.+-@".contains(c) || !c.is_ascii() {
+                    name.push(c);
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            let section = chars.get(i) == Some(&':')
+                && !matches!(chars.get(i + 1), Some(c) if !c.is_whitespace() && *c != '/')
+                && ["types", "dimensions", "variables", "data", "group"].contains(&name.as_str());
+            if section {
+                tokens.push((Token::Section(name), line));
+                i += 1;
+            } else if ["NaN", "NaNf", "Infinity", "Infinityf"].contains(&name.as_str()) {
+                tokens.push((Token::Number(name), line));
+            } else {
+                tokens.push((Token::Name(name), line));
+            }
+        } else {
+            return Err(err(line, &format!("unexpected character {:?}", c)));
+        }
+    }
+    tokens.push((Token::Eof, line));
+    Ok(tokens)
+}
+
+fn push_char(s: &mut Vec<u8>, c: char) {
+    s.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+}
+
+fn is_hex(chars: &[char]) -> bool {
+    let offset = usize::from(matches!(chars.first(), Some('-' | '+')));
+    chars.len() > offset + 1 && chars[offset] == '0' && "xX".contains(chars[offset + 1])
+}
+
+/// A parsed numeric constant
+#[derive(Debug, Clone, Copy)]
+enum Number {
+    Int(i128),
+    Float(f64),
+}
+
+/// Parses a number into its value and the type given by the suffix
+fn number(text: &str) -> Option<(Number, Option<nc_type>)> {
+    let (text, float_suffix) = match text.strip_suffix('f') {
+        Some(t) if t.ends_with("NaN") || t.ends_with("Infinity") => (t, Some(NC_FLOAT)),
+        _ => (text, None),
+    };
+    match text.trim_start_matches('+') {
+        "NaN" | "-NaN" => return Some((Number::Float(f64::NAN), float_suffix)),
+        "Infinity" => return Some((Number::Float(f64::INFINITY), float_suffix)),
+        "-Infinity" => return Some((Number::Float(f64::NEG_INFINITY), float_suffix)),
+        _ => {}
+    }
+
+    let chars = text.chars().collect::<Vec<_>>();
+    let hex = is_hex(&chars);
+    let suffix_chars: &[char] = if hex {
+        &['u', 'U', 'l', 'L', 's', 'S']
+    } else {
+        &['u', 'U', 'l', 'L', 's', 'S', 'b', 'B', 'f', 'F', 'd', 'D']
+    };
+    let digits = text.trim_end_matches(suffix_chars);
+    let suffix = text[digits.len()..].to_ascii_lowercase();
+    let xtype = match suffix.as_str() {
+        "" => None,
+        "b" => Some(NC_BYTE),
+        "s" => Some(NC_SHORT),
+        "l" | "ll" => Some(NC_INT64),
+        "u" => Some(NC_UINT),
+        "ub" => Some(NC_UBYTE),
+        "us" => Some(NC_USHORT),
+        "ul" | "ull" => Some(NC_UINT64),
+        "f" => Some(NC_FLOAT),
+        "d" => Some(NC_DOUBLE),
+        _ => return None,
+    };
+
+    let value = if hex {
+        let (negative, digits) = match digits.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, digits.trim_start_matches('+')),
+        };
+        let value = i128::from_str_radix(&digits[2..], 16).ok()?;
+        Number::Int(if negative { -value } else { value })
+    } else if let Ok(value) = digits.trim_start_matches('+').parse::<i128>() {
+        Number::Int(value)
+    } else {
+        Number::Float(digits.parse::<f64>().ok()?)
+    };
+    match (value, xtype) {
+        (Number::Int(v), Some(NC_FLOAT | NC_DOUBLE)) => Some((Number::Float(v as f64), xtype)),
+        (Number::Float(_), Some(t)) if t != NC_FLOAT && t != NC_DOUBLE => None,
+        _ => Some((value, xtype.or(float_suffix))),
+    }
+}
+
+/// A value in an attribute or data section
+#[derive(Debug, Clone)]
+enum Value {
+    Number(String),
+    Str(Vec<u8>),
+    /// Enum members, `_` for fill values, and `NIL`
+    Name(String),
+    List(Vec<Value>),
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    unlimited: Vec<nc_type>,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
+    }
+
+    fn peek_at(&self, n: usize) -> &Token {
+        &self.tokens[(self.pos + n).min(self.tokens.len() - 1)].0
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].0.clone();
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn error(&self, expected: &str) -> error::Error {
+        let (token, line) = &self.tokens[self.pos];
+        let found = match token {
+            Token::Name(n) | Token::Number(n) => n.clone(),
+            Token::Section(n) => format!("{}:", n),
+            Token::Str(s) => format!("{:?}", String::from_utf8_lossy(s)),
+            Token::Punct(c) => c.to_string(),
+            Token::Eof => "end of input".to_string(),
+        };
+        error::Error::Str(format!(
+            "line {}: expected {}, found {}",
+            line, expected, found
+        ))
+    }
+
+    fn expect(&mut self, c: char) -> error::Result<()> {
+        if self.peek() == &Token::Punct(c) {
+            self.next();
+            Ok(())
+        } else {
+            Err(self.error(&format!("'{}'", c)))
+        }
+    }
+
+    fn accept(&mut self, c: char) -> bool {
+        if self.peek() == &Token::Punct(c) {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn name(&mut self) -> error::Result<String> {
+        match self.peek().clone() {
+            Token::Name(name) => {
+                self.next();
+                Ok(name)
+            }
+            _ => Err(self.error("a name")),
+        }
+    }
+
+    fn expect_name(&mut self, keyword: &str) -> error::Result<()> {
+        match self.peek() {
+            Token::Name(name) if name == keyword => {
+                self.next();
+                Ok(())
+            }
+            _ => Err(self.error(keyword)),
+        }
+    }
+
+    fn integer(&mut self) -> error::Result<usize> {
+        if let Token::Number(text) = self.peek().clone() {
+            if let Some((Number::Int(value), _)) = number(&text) {
+                if let Ok(value) = value.try_into() {
+                    self.next();
+                    return Ok(value);
+                }
+            }
+        }
+        Err(self.error("a positive integer"))
+    }
+
+    fn section(&mut self, name: &str) -> bool {
+        if matches!(self.peek(), Token::Section(s) if s == name) {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn at_section_end(&self) -> bool {
+        matches!(
+            self.peek(),
+            Token::Section(_) | Token::Punct('}') | Token::Eof
+        )
+    }
+
+    fn group(&mut self, ncid: nc_type) -> error::Result<()> {
+        if self.section("types") {
+            while !self.at_section_end() {
+                self.type_declaration(ncid)?;
+            }
+        }
+        if self.section("dimensions") {
+            while !self.at_section_end() {
+                self.dimension_declaration(ncid)?;
+            }
+        }
+        // Attributes are also accepted without a leading `variables:`
+        self.section("variables");
+        while !self.at_section_end() {
+            self.variable_declaration(ncid)?;
+        }
+        if self.section("data") {
+            enddef(ncid)?;
+            while !self.at_section_end() {
+                self.data(ncid)?;
+            }
+        }
+        while self.section("group") {
+            let name = cstring(self.name()?)?;
+            self.expect('{')?;
+            let mut child = 0;
+            unsafe {
+                error::checked(super::super::with_lock(|| {
+                    nc_def_grp(ncid, name.as_ptr(), &mut child)
+                }))?;
+            }
+            self.group(child)?;
+            self.expect('}')?;
+        }
+        Ok(())
+    }
+
+    fn type_declaration(&mut self, ncid: nc_type) -> error::Result<()> {
+        let mut newtype = 0;
+        match self.peek().clone() {
+            Token::Name(n) if n == "compound" => {
+                self.next();
+                let name = cstring(self.name()?)?;
+                self.expect('{')?;
+                let mut fields = Vec::new();
+                while !self.accept('}') {
+                    let fieldtype = self.type_name(ncid)?;
+                    loop {
+                        let fieldname = cstring(self.name()?)?;
+                        let mut dims = Vec::new();
+                        if self.accept('(') {
+                            loop {
+                                dims.push(self.integer()?.try_into()?);
+                                if !self.accept(',') {
+                                    break;
+                                }
+                            }
+                            self.expect(')')?;
+                        }
+                        fields.push((fieldname, fieldtype, dims));
+                        if !self.accept(',') {
+                            break;
+                        }
+                    }
+                    self.expect(';')?;
+                }
+                self.accept(';');
+
+                // Fields are laid out like a C struct
+                let mut offsets = Vec::with_capacity(fields.len());
+                let mut size = 0;
+                let mut max_align = 1;
+                for (_, fieldtype, dims) in &fields {
+                    let (fieldsize, align) = layout(ncid, *fieldtype)?;
+                    max_align = max_align.max(align);
+                    size += (align - size % align) % align;
+                    offsets.push(size);
+                    size += fieldsize * dims.iter().product::<nc_type>() as usize;
+                }
+                size += (max_align - size % max_align) % max_align;
+
+                unsafe {
+                    error::checked(super::super::with_lock(|| {
+                        nc_def_compound(ncid, size, name.as_ptr(), &mut newtype)
+                    }))?;
+                }
+                for ((fieldname, fieldtype, dims), offset) in fields.iter().zip(offsets) {
+                    unsafe {
+                        error::checked(super::super::with_lock(|| {
+                            if dims.is_empty() {
+                                nc_insert_compound(
+                                    ncid,
+                                    newtype,
+                                    fieldname.as_ptr(),
+                                    offset,
+                                    *fieldtype,
+                                )
+                            } else {
+                                nc_insert_array_compound(
+                                    ncid,
+                                    newtype,
+                                    fieldname.as_ptr(),
+                                    offset,
+                                    *fieldtype,
+                                    dims.len() as _,
+                                    dims.as_ptr(),
+                                )
+                            }
+                        }))?;
+                    }
+                }
+            }
+            Token::Name(n) if n == "opaque" => {
+                self.next();
+                self.expect('(')?;
+                let size = self.integer()?;
+                self.expect(')')?;
+                let name = cstring(self.name()?)?;
+                self.expect(';')?;
+                unsafe {
+                    error::checked(super::super::with_lock(|| {
+                        nc_def_opaque(ncid, size, name.as_ptr(), &mut newtype)
+                    }))?;
+                }
+            }
+            _ => {
+                let base = if matches!(self.peek(), Token::Name(n) if n == "enum") {
+                    NC_INT
+                } else {
+                    self.type_name(ncid)?
+                };
+                if self.accept('(') {
+                    self.expect('*')?;
+                    self.expect(')')?;
+                    let name = cstring(self.name()?)?;
+                    self.expect(';')?;
+                    unsafe {
+                        error::checked(super::super::with_lock(|| {
+                            nc_def_vlen(ncid, name.as_ptr(), base, &mut newtype)
+                        }))?;
+                    }
+                } else {
+                    self.expect_name("enum")?;
+                    let name = cstring(self.name()?)?;
+                    unsafe {
+                        error::checked(super::super::with_lock(|| {
+                            nc_def_enum(ncid, base, name.as_ptr(), &mut newtype)
+                        }))?;
+                    }
+                    self.expect('{')?;
+                    loop {
+                        let member = cstring(self.name()?)?;
+                        self.expect('=')?;
+                        let value = match self.next() {
+                            Token::Number(text) => text,
+                            _ => return Err(self.error("an integer")),
+                        };
+                        let mut bytes = Vec::new();
+                        Encoder::default().encode(
+                            &Type::Atomic(base),
+                            &Value::Number(value),
+                            None,
+                            &mut bytes,
+                        )?;
+                        unsafe {
+                            error::checked(super::super::with_lock(|| {
+                                nc_insert_enum(
+                                    ncid,
+                                    newtype,
+                                    member.as_ptr(),
+                                    bytes.as_ptr().cast(),
+                                )
+                            }))?;
+                        }
+                        if !self.accept(',') {
+                            break;
+                        }
+                    }
+                    self.expect('}')?;
+                    self.expect(';')?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn dimension_declaration(&mut self, ncid: nc_type) -> error::Result<()> {
+        loop {
+            let name = cstring(self.name()?)?;
+            self.expect('=')?;
+            let unlimited =
+                matches!(self.peek(), Token::Name(n) if n == "UNLIMITED" || n == "unlimited");
+            let len = if unlimited {
+                self.next();
+                NC_UNLIMITED as usize
+            } else {
+                self.integer()?
+            };
+            let mut dimid = 0;
+            unsafe {
+                error::checked(super::super::with_lock(|| {
+                    nc_def_dim(ncid, name.as_ptr(), len, &mut dimid)
+                }))?;
+            }
+            if unlimited {
+                self.unlimited.push(dimid);
+            }
+            if !self.accept(',') {
+                break;
+            }
+        }
+        self.expect(';')
+    }
+
+    /// Parses a variable or an attribute declaration
+    fn variable_declaration(&mut self, ncid: nc_type) -> error::Result<()> {
+        // Attributes are `[type] [variable]:name = values ;`
+        let attribute = match (self.peek(), self.peek_at(1), self.peek_at(2)) {
+            (Token::Punct(':'), _, _) => Some((None, None)),
+            // `string :name` is a typed global attribute, unless
+            // a variable has the same name as the type
+            (Token::Name(first), Token::Punct(':'), _)
+                if !(is_type(ncid, first) && varid(ncid, first).is_err()) =>
+            {
+                Some((None, Some(self.name()?)))
+            }
+            (Token::Name(_), Token::Punct(':'), _) => Some((Some(self.type_name(ncid)?), None)),
+            (Token::Name(_), Token::Name(_), Token::Punct(':')) => {
+                let xtype = self.type_name(ncid)?;
+                Some((Some(xtype), Some(self.name()?)))
+            }
+            _ => None,
+        };
+        if let Some((xtype, variable)) = attribute {
+            self.expect(':')?;
+            let name = self.name()?;
+            self.expect('=')?;
+            let values = self.values()?;
+            self.expect(';')?;
+            let varid = match variable {
+                None => NC_GLOBAL,
+                Some(variable) => varid(ncid, &variable)?,
+            };
+            return put_attribute(ncid, varid, &name, xtype, &values);
+        }
+
+        let xtype = self.type_name(ncid)?;
+        loop {
+            let name = cstring(self.name()?)?;
+            let mut dimids = Vec::new();
+            if self.accept('(') {
+                loop {
+                    let dim = self.name()?;
+                    let dimid = super::super::dimension::from_name_toid(ncid, &dim)?
+                        .ok_or_else(|| error::Error::NotFound(format!("dimension {}", dim)))?;
+                    dimids.push(dimid);
+                    if !self.accept(',') {
+                        break;
+                    }
+                }
+                self.expect(')')?;
+            }
+            let mut varid = 0;
+            unsafe {
+                error::checked(super::super::with_lock(|| {
+                    nc_def_var(
+                        ncid,
+                        name.as_ptr(),
+                        xtype,
+                        dimids.len() as _,
+                        dimids.as_ptr(),
+                        &mut varid,
+                    )
+                }))?;
+            }
+            if !self.accept(',') {
+                break;
+            }
+        }
+        self.expect(';')
+    }
+
+    fn type_name(&mut self, ncid: nc_type) -> error::Result<nc_type> {
+        let name = self.name()?;
+        let xtype = match name.as_str() {
+            "byte" => NC_BYTE,
+            "char" => NC_CHAR,
+            "short" => NC_SHORT,
+            "int" | "long" => NC_INT,
+            "float" | "real" => NC_FLOAT,
+            "double" => NC_DOUBLE,
+            "ubyte" => NC_UBYTE,
+            "ushort" => NC_USHORT,
+            "uint" => NC_UINT,
+            "int64" => NC_INT64,
+            "uint64" => NC_UINT64,
+            "string" => NC_STRING,
+            _ => {
+                let cname = cstring(name.clone())?;
+                let mut xtype = 0;
+                let e = unsafe {
+                    super::super::with_lock(|| nc_inq_typeid(ncid, cname.as_ptr(), &mut xtype))
+                };
+                if e != NC_NOERR {
+                    self.pos -= 1;
+                    return Err(self.error("a type"));
+                }
+                xtype
+            }
+        };
+        Ok(xtype)
+    }
+
+    fn values(&mut self) -> error::Result<Vec<Value>> {
+        let mut values = Vec::new();
+        loop {
+            values.push(self.value()?);
+            if !self.accept(',') {
+                break;
+            }
+        }
+        Ok(values)
+    }
+
+    fn value(&mut self) -> error::Result<Value> {
+        match self.next() {
+            Token::Number(text) => Ok(Value::Number(text)),
+            Token::Str(s) => Ok(Value::Str(s)),
+            Token::Name(name) => Ok(Value::Name(name)),
+            Token::Punct('{') => {
+                if self.accept('}') {
+                    return Ok(Value::List(Vec::new()));
+                }
+                let values = self.values()?;
+                self.expect('}')?;
+                Ok(Value::List(values))
+            }
+            _ => {
+                self.pos -= 1;
+                Err(self.error("a value"))
+            }
+        }
+    }
+
+    fn data(&mut self, ncid: nc_type) -> error::Result<()> {
+        let name = self.name()?;
+        self.expect('=')?;
+        let values = self.values()?;
+        self.expect(';')?;
+        let varid = varid(ncid, &name)?;
+        self.put_data(ncid, varid, values).map_err(|e| match e {
+            error::Error::Str(msg) => error::Error::Str(format!("variable {}: {}", name, msg)),
+            e => e,
+        })
+    }
+
+    fn put_data(&self, ncid: nc_type, varid: nc_type, values: Vec<Value>) -> error::Result<()> {
+        let (_, xtype, dimids) = super::inq_var(ncid, varid)?;
+        let shape = dimids
+            .iter()
+            .map(|&dimid| {
+                let mut len = 0;
+                unsafe {
+                    error::checked(super::super::with_lock(|| {
+                        nc_inq_dimlen(ncid, dimid, &mut len)
+                    }))?;
+                }
+                Ok(len)
+            })
+            .collect::<error::Result<Vec<usize>>>()?;
+        let typ = describe(ncid, xtype)?;
+        let mut size = 0;
+        unsafe {
+            error::checked(super::super::with_lock(|| {
+                nc_inq_type(ncid, xtype, std::ptr::null_mut(), &mut size)
+            }))?;
+        }
+
+        let mut encoder = Encoder::default();
+        let mut bytes = Vec::new();
+        let mut fill = vec![0_u8; size];
+        if xtype != NC_STRING && !matches!(typ, Type::Vlen { .. }) {
+            unsafe {
+                error::checked(super::super::with_lock(|| {
+                    nc_inq_var_fill(ncid, varid, std::ptr::null_mut(), fill.as_mut_ptr().cast())
+                }))?;
+            }
+        }
+
+        if xtype == NC_CHAR {
+            // Strings fill up the last dimension, and are padded to its length
+            let rowlen = shape.last().copied().filter(|&l| l > 0).unwrap_or(1);
+            for value in flatten(values) {
+                match value {
+                    Value::Str(s) => {
+                        bytes.extend_from_slice(&s);
+                        let rem = s.len() % rowlen;
+                        if s.is_empty() || rem != 0 {
+                            let pad = if s.is_empty() { rowlen } else { rowlen - rem };
+                            bytes.resize(bytes.len() + pad, 0);
+                        }
+                    }
+                    value => encoder.encode(&typ, &value, Some(&fill), &mut bytes)?,
+                }
+            }
+        } else {
+            let values = match typ {
+                Type::Compound { .. } | Type::Vlen { .. } => values,
+                _ => flatten(values),
+            };
+            for value in &values {
+                encoder.encode(&typ, value, Some(&fill), &mut bytes)?;
+            }
+        }
+        let nvalues = bytes.len() / size;
+
+        // The first unlimited dimension grows to hold all the values
+        let mut count = shape.clone();
+        if let Some(first) = dimids.first() {
+            if self.unlimited.contains(first) || shape[0] == 0 {
+                let inner = shape[1..].iter().product::<usize>().max(1);
+                count[0] = nvalues / inner + usize::from(nvalues % inner != 0);
+            }
+        }
+        let total = count.iter().product::<usize>();
+        if nvalues > total {
+            return Err(error::Error::Str(format!(
+                "too many values, expected at most {} but found {}",
+                total, nvalues
+            )));
+        }
+        while bytes.len() < total * size {
+            encoder.encode(&typ, &Value::Name("_".into()), Some(&fill), &mut bytes)?;
+        }
+        if total == 0 {
+            return Ok(());
+        }
+
+        // u64 ensures the buffer is aligned for all types
+        let mut buffer = vec![0_u64; bytes.len() / 8 + 1];
+        unsafe {
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), buffer.as_mut_ptr().cast(), bytes.len());
+        }
+        let start = vec![0; count.len()];
+        unsafe {
+            error::checked(super::super::with_lock(|| {
+                nc_put_vara(
+                    ncid,
+                    varid,
+                    start.as_ptr(),
+                    count.as_ptr(),
+                    buffer.as_ptr().cast(),
+                )
+            }))
+        }
+    }
+}
+
+/// Values may be grouped by braces, which has no meaning for
+/// types other than compounds and variable length arrays
+fn flatten(values: Vec<Value>) -> Vec<Value> {
+    let mut flat = Vec::with_capacity(values.len());
+    for value in values {
+        match value {
+            Value::List(values) => flat.extend(flatten(values)),
+            value => flat.push(value),
+        }
+    }
+    flat
+}
+
+fn is_type(ncid: nc_type, name: &str) -> bool {
+    let basic = [
+        "byte", "char", "short", "int", "long", "float", "real", "double", "ubyte", "ushort",
+        "uint", "int64", "uint64", "string",
+    ];
+    if basic.contains(&name) {
+        return true;
+    }
+    let cname = match CString::new(name) {
+        Ok(cname) => cname,
+        Err(_) => return false,
+    };
+    let mut xtype = 0;
+    let e = unsafe { super::super::with_lock(|| nc_inq_typeid(ncid, cname.as_ptr(), &mut xtype)) };
+    e == NC_NOERR
+}
+
+fn cstring(name: String) -> error::Result<CString> {
+    Ok(CString::new(name)?)
+}
+
+fn varid(ncid: nc_type, name: &str) -> error::Result<nc_type> {
+    let cname = cstring(name.to_string())?;
+    let mut varid = 0;
+    let e = unsafe { super::super::with_lock(|| nc_inq_varid(ncid, cname.as_ptr(), &mut varid)) };
+    if e == NC_ENOTVAR {
+        return Err(error::Error::NotFound(format!("variable {}", name)));
+    }
+    error::checked(e)?;
+    Ok(varid)
+}
+
+/// Size and alignment of a type when used in a compound type
+fn layout(ncid: nc_type, xtype: nc_type) -> error::Result<(usize, usize)> {
+    let mut size = 0;
+    unsafe {
+        error::checked(super::super::with_lock(|| {
+            nc_inq_type(ncid, xtype, std::ptr::null_mut(), &mut size)
+        }))?;
+    }
+    let align = match describe(ncid, xtype)? {
+        Type::Atomic(_) => size,
+        Type::Enum { base, .. } => layout(ncid, base)?.1,
+        Type::Opaque { .. } => 1,
+        Type::Vlen { .. } => std::mem::align_of::<nc_vlen_t>(),
+        Type::Compound { fields, .. } => fields
+            .iter()
+            .map(|field| layout(ncid, field.xtype).map(|l| l.1))
+            .try_fold(1, |acc, align| align.map(|a| acc.max(a)))?,
+    };
+    Ok((size, align))
+}
+
+/// Special attributes printed by `ncdump -s`, which
+/// correspond to storage settings of a variable
+fn put_special_attribute(
+    ncid: nc_type,
+    varid: nc_type,
+    name: &str,
+    values: &[Value],
+) -> Option<error::Result<()>> {
+    let text = || match values.first() {
+        Some(Value::Str(s)) => String::from_utf8_lossy(s).to_lowercase(),
+        _ => String::new(),
+    };
+    let integers = || {
+        values
+            .iter()
+            .map(|value| match value {
+                Value::Number(n) => match number(n) {
+                    Some((Number::Int(v), _)) => v.try_into().ok(),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect::<Option<Vec<usize>>>()
+            .ok_or_else(|| error::Error::Str(format!("{} must be integers", name)))
+    };
+    let deflate = |shuffle: Option<bool>, level: Option<nc_type>| {
+        let mut old_shuffle = 0;
+        let mut old_deflate = 0;
+        let mut old_level = 0;
+        unsafe {
+            error::checked(super::super::with_lock(|| {
+                nc_inq_var_deflate(
+                    ncid,
+                    varid,
+                    &mut old_shuffle,
+                    &mut old_deflate,
+                    &mut old_level,
+                )
+            }))?;
+            let shuffle = shuffle.map_or(old_shuffle, nc_type::from);
+            let (deflate, level) = level.map_or((old_deflate, old_level), |l| (1, l));
+            error::checked(super::super::with_lock(|| {
+                nc_def_var_deflate(ncid, varid, shuffle, deflate, level)
+            }))
+        }
+    };
+
+    let result = match name {
+        "_Storage" => match text().as_str() {
+            "contiguous" => unsafe {
+                error::checked(super::super::with_lock(|| {
+                    nc_def_var_chunking(ncid, varid, NC_CONTIGUOUS, std::ptr::null())
+                }))
+            },
+            "compact" => unsafe {
+                error::checked(super::super::with_lock(|| {
+                    nc_def_var_chunking(ncid, varid, NC_COMPACT, std::ptr::null())
+                }))
+            },
+            // Chunk sizes are given by `_ChunkSizes`
+            _ => Ok(()),
+        },
+        "_ChunkSizes" => integers().and_then(|sizes| unsafe {
+            let mut ndims = 0;
+            error::checked(super::super::with_lock(|| {
+                nc_inq_varndims(ncid, varid, &mut ndims)
+            }))?;
+            if sizes.len() != ndims.try_into()? {
+                return Err(error::Error::Str(format!(
+                    "{} must have one size per dimension",
+                    name
+                )));
+            }
+            error::checked(super::super::with_lock(|| {
+                nc_def_var_chunking(ncid, varid, NC_CHUNKED, sizes.as_ptr())
+            }))
+        }),
+        "_DeflateLevel" => integers().and_then(|level| {
+            let level = level.first().copied().unwrap_or(0).try_into()?;
+            deflate(None, Some(level))
+        }),
+        "_Shuffle" => deflate(Some(text() == "true"), None),
+        "_Fletcher32" => unsafe {
+            let checksum = if text() == "true" {
+                NC_FLETCHER32
+            } else {
+                NC_NOCHECKSUM
+            };
+            error::checked(super::super::with_lock(|| {
+                nc_def_var_fletcher32(ncid, varid, checksum)
+            }))
+        },
+        "_Endianness" => {
+            let endian = match text().as_str() {
+                "little" => NC_ENDIAN_LITTLE,
+                "big" => NC_ENDIAN_BIG,
+                _ => NC_ENDIAN_NATIVE,
+            };
+            unsafe {
+                error::checked(super::super::with_lock(|| {
+                    nc_def_var_endian(ncid, varid, endian)
+                }))
+            }
+        }
+        "_NoFill" => unsafe {
+            let no_fill = nc_type::from(text() == "true");
+            error::checked(super::super::with_lock(|| {
+                nc_def_var_fill(ncid, varid, no_fill, std::ptr::null())
+            }))
+        },
+        // Informative attributes of the file
+        "_Format" | "_NCProperties" | "_IsNetcdf4" | "_SuperblockVersion" => Ok(()),
+        _ => return None,
+    };
+    Some(result)
+}
+
+fn put_attribute(
+    ncid: nc_type,
+    varid: nc_type,
+    name: &str,
+    xtype: Option<nc_type>,
+    values: &[Value],
+) -> error::Result<()> {
+    if let Some(result) = put_special_attribute(ncid, varid, name, values) {
+        return result;
+    }
+
+    // Untyped attributes take the type of the values
+    let xtype = xtype.unwrap_or_else(|| {
+        let mut xtype = None;
+        for value in values {
+            let t = match value {
+                Value::Str(_) => NC_CHAR,
+                Value::Number(text) => match number(text) {
+                    Some((_, Some(t))) => t,
+                    Some((Number::Float(_), None)) => NC_DOUBLE,
+                    _ => NC_INT,
+                },
+                _ => NC_INT,
+            };
+            xtype = match xtype {
+                None => Some(t),
+                Some(NC_INT) if t == NC_DOUBLE => Some(NC_DOUBLE),
+                Some(x) => Some(x),
+            };
+        }
+        xtype.unwrap_or(NC_CHAR)
+    });
+
+    let mut bytes = Vec::new();
+    let mut encoder = Encoder::default();
+    if xtype == NC_CHAR {
+        for value in flatten(values.to_vec()) {
+            match value {
+                Value::Str(s) => bytes.extend_from_slice(&s),
+                value => encoder.encode(&Type::Atomic(NC_CHAR), &value, None, &mut bytes)?,
+            }
+        }
+    } else {
+        let typ = describe(ncid, xtype)?;
+        let values = match typ {
+            Type::Compound { .. } | Type::Vlen { .. } => values.to_vec(),
+            _ => flatten(values.to_vec()),
+        };
+        for value in &values {
+            encoder.encode(&typ, value, None, &mut bytes)?;
+        }
+    }
+    let mut size = 0;
+    unsafe {
+        error::checked(super::super::with_lock(|| {
+            nc_inq_type(ncid, xtype, std::ptr::null_mut(), &mut size)
+        }))?;
+    }
+    let len = bytes.len() / size;
+
+    let mut buffer = vec![0_u64; bytes.len() / 8 + 1];
+    unsafe {
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), buffer.as_mut_ptr().cast(), bytes.len());
+    }
+    let cname = cstring(name.to_string())?;
+    unsafe {
+        error::checked(super::super::with_lock(|| {
+            nc_put_att(
+                ncid,
+                varid,
+                cname.as_ptr(),
+                xtype,
+                len,
+                buffer.as_ptr().cast(),
+            )
+        }))
+    }
+}
+
+/// Converts values to their memory representation, keeping
+/// strings and variable length arrays alive until written
+#[derive(Default)]
+struct Encoder {
+    strings: Vec<CString>,
+    buffers: Vec<Vec<u64>>,
+}
+
+impl Encoder {
+    fn encode(
+        &mut self,
+        typ: &Type,
+        value: &Value,
+        fill: Option<&[u8]>,
+        out: &mut Vec<u8>,
+    ) -> error::Result<()> {
+        let mismatch =
+            |expected: &str| error::Error::Str(format!("expected {}, found {:?}", expected, value));
+
+        if let (Value::Name(name), Some(fill)) = (value, fill) {
+            if name == "_" {
+                match typ {
+                    Type::Atomic(NC_STRING) => {
+                        return self.encode(typ, &Value::Str(Vec::new()), None, out)
+                    }
+                    Type::Vlen { .. } => {
+                        return self.encode(typ, &Value::List(Vec::new()), None, out)
+                    }
+                    _ => {
+                        out.extend_from_slice(fill);
+                        return Ok(());
+                    }
+                }
+            }
+        }
+
+        match typ {
+            Type::Atomic(NC_STRING) => {
+                let ptr = match value {
+                    Value::Name(n) if n == "NIL" => std::ptr::null(),
+                    Value::Str(s) => {
+                        self.strings.push(CString::new(s.clone())?);
+                        self.strings.last().unwrap().as_ptr()
+                    }
+                    _ => return Err(mismatch("a string")),
+                };
+                out.extend_from_slice(&(ptr as usize).to_ne_bytes());
+            }
+            Type::Atomic(NC_CHAR) => match value {
+                Value::Str(s) if s.len() <= 1 => out.push(s.first().copied().unwrap_or(0)),
+                _ => return Err(mismatch("a single character")),
+            },
+            &Type::Atomic(xtype) => {
+                let parsed = match value {
+                    Value::Number(text) => number(text),
+                    _ => None,
+                };
+                let value = match parsed {
+                    Some((value, _)) => value,
+                    None => return Err(mismatch("a number")),
+                };
+                encode_number(xtype, value, out);
+            }
+            Type::Enum { base, members } => {
+                let value = match value {
+                    Value::Name(name) => members
+                        .iter()
+                        .find(|(member, _)| member == name)
+                        .map(|(_, value)| Number::Int((*value).into()))
+                        .ok_or_else(|| mismatch("an enum member"))?,
+                    Value::Number(text) => number(text)
+                        .map(|(value, _)| value)
+                        .ok_or_else(|| mismatch("an enum member"))?,
+                    _ => return Err(mismatch("an enum member")),
+                };
+                encode_number(*base, value, out);
+            }
+            Type::Opaque { size } => {
+                let text = match value {
+                    Value::Number(text) if is_hex(&text.chars().collect::<Vec<_>>()) => &text[2..],
+                    _ => return Err(mismatch("a hexadecimal constant")),
+                };
+                let mut bytes = text
+                    .as_bytes()
+                    .chunks(2)
+                    .map(|pair| {
+                        std::str::from_utf8(pair)
+                            .ok()
+                            .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                            .ok_or_else(|| mismatch("a hexadecimal constant"))
+                    })
+                    .collect::<error::Result<Vec<u8>>>()?;
+                if bytes.len() > *size {
+                    return Err(mismatch(&format!("at most {} bytes", size)));
+                }
+                bytes.resize(*size, 0);
+                out.extend_from_slice(&bytes);
+            }
+            Type::Vlen { base, size } => {
+                let values = match value {
+                    Value::List(values) => values.clone(),
+                    value => vec![value.clone()],
+                };
+                let values = match **base {
+                    Type::Compound { .. } | Type::Vlen { .. } => values,
+                    _ => flatten(values),
+                };
+                let mut bytes = Vec::with_capacity(values.len() * size);
+                for value in &values {
+                    self.encode(base, value, None, &mut bytes)?;
+                }
+                let mut buffer = vec![0_u64; bytes.len() / 8 + 1];
+                unsafe {
+                    std::ptr::copy_nonoverlapping(
+                        bytes.as_ptr(),
+                        buffer.as_mut_ptr().cast(),
+                        bytes.len(),
+                    );
+                }
+                let vlen = nc_vlen_t {
+                    len: values.len(),
+                    p: buffer.as_mut_ptr().cast(),
+                };
+                self.buffers.push(buffer);
+                let vlen = unsafe {
+                    std::slice::from_raw_parts(
+                        std::ptr::addr_of!(vlen).cast::<u8>(),
+                        std::mem::size_of::<nc_vlen_t>(),
+                    )
+                };
+                out.extend_from_slice(vlen);
+            }
+            Type::Compound { fields, size } => {
+                let values = match value {
+                    Value::List(values) => values,
+                    _ => return Err(mismatch("a compound value")),
+                };
+                let mut element = vec![0_u8; *size];
+                let mut values = values.iter();
+                for field in fields {
+                    let nelems = field.dims.iter().product::<usize>();
+                    let mut bytes = Vec::with_capacity(nelems * field.size);
+                    if field.xtype == NC_CHAR && !field.dims.is_empty() {
+                        if let Some(Value::Str(s)) = values.next() {
+                            bytes.extend_from_slice(s);
+                        }
+                    } else if field.dims.is_empty() {
+                        let value = values.next().ok_or_else(|| mismatch("more fields"))?;
+                        self.encode(&field.typ, value, None, &mut bytes)?;
+                    } else {
+                        // Array fields are either grouped by braces or given in full
+                        let array = match values.clone().next() {
+                            Some(Value::List(array)) => {
+                                values.next();
+                                flatten(array.clone())
+                            }
+                            _ => values.by_ref().take(nelems).cloned().collect(),
+                        };
+                        for value in &array {
+                            self.encode(&field.typ, value, None, &mut bytes)?;
+                        }
+                    }
+                    bytes.resize(nelems * field.size, 0);
+                    element[field.offset..field.offset + bytes.len()].copy_from_slice(&bytes);
+                }
+                out.extend_from_slice(&element);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn encode_number(xtype: nc_type, value: Number, out: &mut Vec<u8>) {
+    let int = match value {
+        Number::Int(v) => v,
+        Number::Float(v) => v as i128,
+    };
+    let float = match value {
+        Number::Int(v) => v as f64,
+        Number::Float(v) => v,
+    };
+    match xtype {
+        NC_BYTE => out.extend_from_slice(&(int as i8).to_ne_bytes()),
+        NC_UBYTE => out.extend_from_slice(&(int as u8).to_ne_bytes()),
+        NC_SHORT => out.extend_from_slice(&(int as i16).to_ne_bytes()),
+        NC_USHORT => out.extend_from_slice(&(int as u16).to_ne_bytes()),
+        NC_INT => out.extend_from_slice(&(int as i32).to_ne_bytes()),
+        NC_UINT => out.extend_from_slice(&(int as u32).to_ne_bytes()),
+        NC_INT64 => out.extend_from_slice(&(int as i64).to_ne_bytes()),
+        NC_UINT64 => out.extend_from_slice(&(int as u64).to_ne_bytes()),
+        NC_FLOAT => out.extend_from_slice(&(float as f32).to_ne_bytes()),
+        NC_DOUBLE => out.extend_from_slice(&float.to_ne_bytes()),
+        _ => {}
+    }
+}
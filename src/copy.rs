@@ -0,0 +1,795 @@
+//! Copy variables and files between datasets
+//!
+//! All dimensions, user defined types, groups, attributes, and
+//! variables are recreated in the destination, together with the
+//! storage settings when both files are `netCDF-4` files. Data is
+//! streamed through a buffer of bounded size, so files larger
+//! than the available memory can be copied.
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let src = netcdf::open("classic.nc")?;
+//! let mut dst = netcdf::create("netcdf4.nc")?;
+//!
+//! let mut options = netcdf::copy::CopyOptions::default();
+//! options.deflate = Some(netcdf::DeflateOptions {
+//!     level: 4,
+//!     shuffle: true,
+//! });
+//! options.chunking.insert("time".to_string(), 1);
+//! netcdf::copy::copy_file(&src, &mut dst, &options)?;
+//! # Ok(()) }
+//! ```
+
+#![allow(clippy::similar_names)]
+use super::error;
+use super::file::{File, Format, MutableFile};
+use super::types::VariableType;
+use super::utils::{enddef, name_to_str, needs_reclaim, reclaim_values, redef};
+use super::variable::{DeflateOptions, Filter, Storage, Variable, VariableMut};
+use netcdf_sys::*;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::marker::PhantomData;
+
+/// Options controlling how variables are copied, storage
+/// settings only apply to `netCDF-4` destinations
+#[derive(Debug, Clone)]
+pub struct CopyOptions {
+    /// Chunk length to use for the dimensions with the given
+    /// names. Dimensions not in the map keep the chunking of
+    /// the source variable
+    pub chunking: HashMap<String, usize>,
+    /// Compress all variables with `deflate`, replacing the
+    /// compression of the source variables
+    pub deflate: Option<DeflateOptions>,
+    /// Filters applied to all variables in addition
+    /// to the filters of the source variables
+    pub filters: Vec<Filter>,
+    /// Maximum number of bytes read into memory at once
+    pub buffer_size: usize,
+}
+
+impl Default for CopyOptions {
+    fn default() -> Self {
+        Self {
+            chunking: HashMap::new(),
+            deflate: None,
+            filters: Vec::new(),
+            buffer_size: 64 * 1024 * 1024,
+        }
+    }
+}
+
+/// Copy the contents of `src` into `dst`
+///
+/// The format of the destination is given by how `dst` was created,
+/// and copying from a `netCDF-4` file with groups or user defined
+/// types into a classic file fails.
+///
+/// # Errors
+///
+/// Names already existing in `dst`, or unsupported types or
+/// storage settings in the destination format
+pub fn copy_file(src: &File, dst: &mut MutableFile, options: &CopyOptions) -> error::Result<()> {
+    let mut copier = Copier {
+        src_nc4: matches!(src.format()?, Format::Netcdf4 | Format::Netcdf4Classic),
+        dst_nc4: matches!(dst.format()?, Format::Netcdf4 | Format::Netcdf4Classic),
+        options,
+        reuse_types: false,
+        types: HashMap::new(),
+        dims: HashMap::new(),
+        variables: Vec::new(),
+    };
+
+    redef(dst.ncid())?;
+    let result = copier.define_group(src.ncid(), dst.ncid());
+    let end = enddef(dst.ncid());
+    result?;
+    end?;
+
+    copier.copy_data(dst.ncid())
+}
+
+/// Copy a single variable with attributes and data into the root
+/// group of `dst`
+///
+/// Dimensions and types are looked up by name, and created if
+/// they do not exist in `dst`
+///
+/// # Errors
+///
+/// The variable already exists in `dst`, a dimension is too
+/// short, a type with the same name differs, or the type is not
+/// supported in the destination format
+pub fn copy_variable(
+    src: &Variable,
+    dst: &mut MutableFile,
+    options: &CopyOptions,
+) -> error::Result<()> {
+    let mut copier = Copier {
+        src_nc4: matches!(
+            Format::from_ncid(src.ncid)?,
+            Format::Netcdf4 | Format::Netcdf4Classic
+        ),
+        dst_nc4: matches!(dst.format()?, Format::Netcdf4 | Format::Netcdf4Classic),
+        options,
+        reuse_types: true,
+        types: HashMap::new(),
+        dims: HashMap::new(),
+        variables: Vec::new(),
+    };
+
+    redef(dst.ncid())?;
+    let result = (|| {
+        for dim in src.dimensions() {
+            let dimid = match super::dimension::from_name(dst.ncid(), &dim.name())? {
+                Some(existing) => {
+                    if !existing.is_unlimited() && existing.len() < dim.len() {
+                        let msg = format!("dimension {} is too short", dim.name());
+                        return Err(msg.into());
+                    }
+                    existing.id.dimid
+                }
+                None => {
+                    let len = if dim.is_unlimited() {
+                        NC_UNLIMITED as usize
+                    } else {
+                        dim.len()
+                    };
+                    define_dimension(dst.ncid(), &dim.name(), len)?
+                }
+            };
+            copier.dims.insert(dim.id.dimid, dimid);
+        }
+        copier.define_variable(src.ncid, src.varid, dst.ncid())
+    })();
+    let end = enddef(dst.ncid());
+    result?;
+    end?;
+
+    copier.copy_data(dst.ncid())
+}
+
+/// State kept while copying, mapping identifiers in
+/// the source to identifiers in the destination
+struct Copier<'o> {
+    src_nc4: bool,
+    dst_nc4: bool,
+    options: &'o CopyOptions,
+    /// Use existing types with the same name in the destination
+    reuse_types: bool,
+    types: HashMap<nc_type, nc_type>,
+    dims: HashMap<nc_type, nc_type>,
+    /// (`src_ncid`, `src_varid`, `dst_ncid`, `dst_varid`)
+    variables: Vec<(nc_type, nc_type, nc_type, nc_type)>,
+}
+
+impl<'o> Copier<'o> {
+    fn define_group(&mut self, src: nc_type, dst: nc_type) -> error::Result<()> {
+        if self.src_nc4 {
+            let mut ntypes = 0;
+            unsafe {
+                error::checked(super::with_lock(|| {
+                    nc_inq_typeids(src, &mut ntypes, std::ptr::null_mut())
+                }))?;
+            }
+            let mut typeids = vec![0; ntypes.try_into()?];
+            unsafe {
+                error::checked(super::with_lock(|| {
+                    nc_inq_typeids(src, std::ptr::null_mut(), typeids.as_mut_ptr())
+                }))?;
+            }
+            for xtype in typeids {
+                self.define_type(src, xtype, dst)?;
+            }
+        }
+
+        let mut ndims = 0;
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_inq_dimids(src, &mut ndims, std::ptr::null_mut(), false.into())
+            }))?;
+        }
+        let mut dimids = vec![0; ndims.try_into()?];
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_inq_dimids(src, std::ptr::null_mut(), dimids.as_mut_ptr(), false.into())
+            }))?;
+        }
+        let mut nunlim = 0;
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_inq_unlimdims(src, &mut nunlim, std::ptr::null_mut())
+            }))?;
+        }
+        let mut unlimdims = vec![0; nunlim.try_into()?];
+        if nunlim > 0 {
+            unsafe {
+                error::checked(super::with_lock(|| {
+                    nc_inq_unlimdims(src, std::ptr::null_mut(), unlimdims.as_mut_ptr())
+                }))?;
+            }
+        }
+        for dimid in dimids {
+            let mut name = [0_u8; NC_MAX_NAME as usize + 1];
+            let mut len = 0;
+            unsafe {
+                error::checked(super::with_lock(|| {
+                    nc_inq_dim(src, dimid, name.as_mut_ptr().cast(), &mut len)
+                }))?;
+            }
+            if unlimdims.contains(&dimid) {
+                len = NC_UNLIMITED as usize;
+            }
+            let newid = define_dimension(dst, name_to_str(&name)?, len)?;
+            self.dims.insert(dimid, newid);
+        }
+
+        copy_attributes(src, NC_GLOBAL, dst, NC_GLOBAL)?;
+
+        let mut nvars = 0;
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_inq_varids(src, &mut nvars, std::ptr::null_mut())
+            }))?;
+        }
+        let mut varids = vec![0; nvars.try_into()?];
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_inq_varids(src, std::ptr::null_mut(), varids.as_mut_ptr())
+            }))?;
+        }
+        for varid in varids {
+            self.define_variable(src, varid, dst)?;
+        }
+
+        if self.src_nc4 {
+            let mut ngroups = 0;
+            unsafe {
+                error::checked(super::with_lock(|| {
+                    nc_inq_grps(src, &mut ngroups, std::ptr::null_mut())
+                }))?;
+            }
+            let mut groups = vec![0; ngroups.try_into()?];
+            unsafe {
+                error::checked(super::with_lock(|| {
+                    nc_inq_grps(src, std::ptr::null_mut(), groups.as_mut_ptr())
+                }))?;
+            }
+            for group in groups {
+                let mut name = [0_u8; NC_MAX_NAME as usize + 1];
+                unsafe {
+                    error::checked(super::with_lock(|| {
+                        nc_inq_grpname(group, name.as_mut_ptr().cast())
+                    }))?;
+                }
+                let mut newgroup = 0;
+                unsafe {
+                    error::checked(super::with_lock(|| {
+                        nc_def_grp(dst, name.as_ptr().cast(), &mut newgroup)
+                    }))?;
+                }
+                self.define_group(group, newgroup)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Recreates a type in the destination, including the types it depends on
+    fn define_type(
+        &mut self,
+        src: nc_type,
+        xtype: nc_type,
+        dst: nc_type,
+    ) -> error::Result<nc_type> {
+        if xtype <= NC_MAX_ATOMIC_TYPE {
+            return Ok(xtype);
+        }
+        if let Some(&newtype) = self.types.get(&xtype) {
+            return Ok(newtype);
+        }
+
+        let mut name = [0_u8; NC_MAX_NAME as usize + 1];
+        let mut size = 0;
+        let mut base = 0;
+        let mut nfields = 0;
+        let mut class = 0;
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_inq_user_type(
+                    src,
+                    xtype,
+                    name.as_mut_ptr().cast(),
+                    &mut size,
+                    &mut base,
+                    &mut nfields,
+                    &mut class,
+                )
+            }))?;
+        }
+
+        if self.reuse_types {
+            let mut newtype = 0;
+            let e = unsafe {
+                super::with_lock(|| nc_inq_typeid(dst, name.as_ptr().cast(), &mut newtype))
+            };
+            if e == NC_NOERR {
+                let mut equal = 0;
+                unsafe {
+                    error::checked(super::with_lock(|| {
+                        nc_inq_type_equal(src, xtype, dst, newtype, &mut equal)
+                    }))?;
+                }
+                if equal == 0 {
+                    return Err(error::Error::TypeMismatch);
+                }
+                self.types.insert(xtype, newtype);
+                return Ok(newtype);
+            }
+        }
+
+        let mut newtype = 0;
+        match class {
+            NC_OPAQUE => unsafe {
+                error::checked(super::with_lock(|| {
+                    nc_def_opaque(dst, size, name.as_ptr().cast(), &mut newtype)
+                }))?;
+            },
+            NC_VLEN => {
+                let base = self.define_type(src, base, dst)?;
+                unsafe {
+                    error::checked(super::with_lock(|| {
+                        nc_def_vlen(dst, name.as_ptr().cast(), base, &mut newtype)
+                    }))?;
+                }
+            }
+            NC_ENUM => {
+                unsafe {
+                    error::checked(super::with_lock(|| {
+                        nc_def_enum(dst, base, name.as_ptr().cast(), &mut newtype)
+                    }))?;
+                }
+                for idx in 0..nfields.try_into()? {
+                    let mut member = [0_u8; NC_MAX_NAME as usize + 1];
+                    // The size of an enum type is the size of its base type
+                    let mut value = vec![0_u8; size];
+                    unsafe {
+                        error::checked(super::with_lock(|| {
+                            nc_inq_enum_member(
+                                src,
+                                xtype,
+                                idx,
+                                member.as_mut_ptr().cast(),
+                                value.as_mut_ptr().cast(),
+                            )
+                        }))?;
+                        error::checked(super::with_lock(|| {
+                            nc_insert_enum(
+                                dst,
+                                newtype,
+                                member.as_ptr().cast(),
+                                value.as_ptr().cast(),
+                            )
+                        }))?;
+                    }
+                }
+            }
+            NC_COMPOUND => {
+                let mut fields = Vec::with_capacity(nfields);
+                for fieldid in 0..nfields.try_into()? {
+                    let mut fieldname = [0_u8; NC_MAX_NAME as usize + 1];
+                    let mut offset = 0;
+                    let mut fieldtype = 0;
+                    let mut ndims = 0;
+                    unsafe {
+                        error::checked(super::with_lock(|| {
+                            nc_inq_compound_field(
+                                src,
+                                xtype,
+                                fieldid,
+                                fieldname.as_mut_ptr().cast(),
+                                &mut offset,
+                                &mut fieldtype,
+                                &mut ndims,
+                                std::ptr::null_mut(),
+                            )
+                        }))?;
+                    }
+                    let mut dim_sizes = vec![0; ndims.try_into()?];
+                    if ndims > 0 {
+                        unsafe {
+                            error::checked(super::with_lock(|| {
+                                nc_inq_compound_field(
+                                    src,
+                                    xtype,
+                                    fieldid,
+                                    std::ptr::null_mut(),
+                                    std::ptr::null_mut(),
+                                    std::ptr::null_mut(),
+                                    std::ptr::null_mut(),
+                                    dim_sizes.as_mut_ptr(),
+                                )
+                            }))?;
+                        }
+                    }
+                    let fieldtype = self.define_type(src, fieldtype, dst)?;
+                    fields.push((fieldname, offset, fieldtype, dim_sizes));
+                }
+
+                unsafe {
+                    error::checked(super::with_lock(|| {
+                        nc_def_compound(dst, size, name.as_ptr().cast(), &mut newtype)
+                    }))?;
+                }
+                for (fieldname, offset, fieldtype, dim_sizes) in fields {
+                    unsafe {
+                        error::checked(super::with_lock(|| {
+                            if dim_sizes.is_empty() {
+                                nc_insert_compound(
+                                    dst,
+                                    newtype,
+                                    fieldname.as_ptr().cast(),
+                                    offset,
+                                    fieldtype,
+                                )
+                            } else {
+                                nc_insert_array_compound(
+                                    dst,
+                                    newtype,
+                                    fieldname.as_ptr().cast(),
+                                    offset,
+                                    fieldtype,
+                                    dim_sizes.len() as _,
+                                    dim_sizes.as_ptr(),
+                                )
+                            }
+                        }))?;
+                    }
+                }
+            }
+            _ => return Err(error::Error::TypeUnknown(xtype)),
+        }
+
+        self.types.insert(xtype, newtype);
+        Ok(newtype)
+    }
+
+    fn define_variable(&mut self, src: nc_type, varid: nc_type, dst: nc_type) -> error::Result<()> {
+        let mut name = [0_u8; NC_MAX_NAME as usize + 1];
+        let mut xtype = 0;
+        let mut ndims = 0;
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_inq_var(
+                    src,
+                    varid,
+                    name.as_mut_ptr().cast(),
+                    &mut xtype,
+                    &mut ndims,
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                )
+            }))?;
+        }
+        let mut dimids = vec![0; ndims.try_into()?];
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_inq_vardimid(src, varid, dimids.as_mut_ptr())
+            }))?;
+        }
+        let name = name_to_str(&name)?;
+
+        let xtype = self.define_type(src, xtype, dst)?;
+        let dimids = dimids
+            .iter()
+            .map(|dimid| {
+                self.dims
+                    .get(dimid)
+                    .copied()
+                    .ok_or_else(|| error::Error::NotFound(format!("dimension of {}", name)))
+            })
+            .collect::<error::Result<Vec<_>>>()?;
+        let cname = super::utils::short_name_to_bytes(name)?;
+        let mut newvarid = 0;
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_def_var(
+                    dst,
+                    cname.as_ptr().cast(),
+                    xtype,
+                    ndims,
+                    dimids.as_ptr(),
+                    &mut newvarid,
+                )
+            }))?;
+        }
+
+        if self.dst_nc4 && ndims > 0 {
+            let source = Variable::from_id(src, varid)?;
+            let destination = Variable::from_id(dst, newvarid)?;
+            self.copy_storage(&source, &mut VariableMut(destination, PhantomData))?;
+        }
+
+        copy_attributes(src, varid, dst, newvarid)?;
+
+        self.variables.push((src, varid, dst, newvarid));
+        Ok(())
+    }
+
+    fn copy_storage(&self, src: &Variable, dst: &mut VariableMut) -> error::Result<()> {
+        let mut filters = Vec::new();
+        let mut fletcher32 = false;
+        let mut deflate = self.options.deflate;
+        if self.src_nc4 {
+            let settings = src.compression_settings()?;
+            fletcher32 = settings.fletcher32;
+            if deflate.is_none() {
+                deflate = settings.deflate_level.map(|level| DeflateOptions {
+                    level,
+                    shuffle: settings.shuffle,
+                });
+                if deflate.is_none() && settings.shuffle {
+                    filters.push(Filter::new(H5Z_FILTER_SHUFFLE, &[]));
+                }
+            }
+            // deflate, shuffle, and fletcher32 are handled above
+            filters.extend(src.filters()?.into_iter().filter(|f| {
+                f.id != H5Z_FILTER_DEFLATE
+                    && f.id != H5Z_FILTER_SHUFFLE
+                    && f.id != H5Z_FILTER_FLETCHER32
+            }));
+        }
+        filters.extend(self.options.filters.iter().cloned());
+
+        // Variable length data can not be filtered
+        let filterable = !matches!(src.vartype(), VariableType::String | VariableType::Vlen(_));
+        let filtered = filterable && (deflate.is_some() || !filters.is_empty() || fletcher32);
+
+        let storage = if self.src_nc4 {
+            src.storage()?
+        } else {
+            Storage::Contiguous
+        };
+        let rechunk = src
+            .dimensions()
+            .iter()
+            .any(|d| self.options.chunking.contains_key(&d.name()));
+        let storage = if rechunk {
+            let chunksizes = src
+                .dimensions()
+                .iter()
+                .enumerate()
+                .map(
+                    |(i, d)| match (self.options.chunking.get(&d.name()), &storage) {
+                        (Some(&len), _) => len.max(1),
+                        (None, Storage::Chunked(chunksizes)) => chunksizes[i],
+                        (None, _) if d.is_unlimited() => 1,
+                        (None, _) => d.len().max(1),
+                    },
+                )
+                .collect();
+            Some(Storage::Chunked(chunksizes))
+        } else {
+            match storage {
+                // Filtered variables are chunked by the library
+                Storage::Contiguous | Storage::Compact if filtered => None,
+                storage => Some(storage),
+            }
+        };
+        if let Some(storage) = storage {
+            dst.set_storage(storage)?;
+        }
+
+        if filterable {
+            if let Some(deflate) = deflate {
+                dst.deflate(deflate)?;
+            }
+            for filter in &filters {
+                if filter.id == H5Z_FILTER_SHUFFLE {
+                    dst.shuffle(true)?;
+                } else {
+                    dst.add_filter(filter)?;
+                }
+            }
+            if fletcher32 {
+                dst.fletcher32(true)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Copies the data of all defined variables, the destination
+    /// must have left define mode
+    fn copy_data(&self, dst: nc_type) -> error::Result<()> {
+        // Every value is written, filling is unnecessary
+        let mut old_fill = 0;
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_set_fill(dst, NC_NOFILL, &mut old_fill)
+            }))?;
+        }
+        let result =
+            self.variables
+                .iter()
+                .try_for_each(|&(src_ncid, src_varid, dst_ncid, dst_varid)| {
+                    copy_values(
+                        src_ncid,
+                        src_varid,
+                        dst_ncid,
+                        dst_varid,
+                        self.options.buffer_size,
+                    )
+                });
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_set_fill(dst, old_fill, std::ptr::null_mut())
+            }))?;
+        }
+        result
+    }
+}
+
+fn define_dimension(ncid: nc_type, name: &str, len: usize) -> error::Result<nc_type> {
+    let cname = super::utils::short_name_to_bytes(name)?;
+    let mut dimid = 0;
+    unsafe {
+        error::checked(super::with_lock(|| {
+            nc_def_dim(ncid, cname.as_ptr().cast(), len, &mut dimid)
+        }))?;
+    }
+    Ok(dimid)
+}
+
+fn copy_attributes(
+    src: nc_type,
+    src_varid: nc_type,
+    dst: nc_type,
+    dst_varid: nc_type,
+) -> error::Result<()> {
+    let mut natts = 0;
+    unsafe {
+        error::checked(super::with_lock(|| {
+            nc_inq_varnatts(src, src_varid, &mut natts)
+        }))?;
+    }
+    for attnum in 0..natts {
+        let mut name = [0_u8; NC_MAX_NAME as usize + 1];
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_inq_attname(src, src_varid, attnum, name.as_mut_ptr().cast())
+            }))?;
+            error::checked(super::with_lock(|| {
+                nc_copy_att(src, src_varid, name.as_ptr().cast(), dst, dst_varid)
+            }))?;
+        }
+    }
+    Ok(())
+}
+
+/// Copies all values of a variable through a buffer
+/// of at most `buffer_size` bytes (or a single element)
+fn copy_values(
+    src: nc_type,
+    src_varid: nc_type,
+    dst: nc_type,
+    dst_varid: nc_type,
+    buffer_size: usize,
+) -> error::Result<()> {
+    let mut xtype = 0;
+    let mut ndims = 0;
+    unsafe {
+        error::checked(super::with_lock(|| {
+            nc_inq_var(
+                src,
+                src_varid,
+                std::ptr::null_mut(),
+                &mut xtype,
+                &mut ndims,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            )
+        }))?;
+    }
+    let mut dimids = vec![0; ndims.try_into()?];
+    unsafe {
+        error::checked(super::with_lock(|| {
+            nc_inq_vardimid(src, src_varid, dimids.as_mut_ptr())
+        }))?;
+    }
+    let shape = dimids
+        .iter()
+        .map(|&dimid| {
+            let mut len = 0;
+            unsafe {
+                error::checked(super::with_lock(|| nc_inq_dimlen(src, dimid, &mut len)))?;
+            }
+            Ok(len)
+        })
+        .collect::<error::Result<Vec<usize>>>()?;
+    if shape.contains(&0) {
+        return Ok(());
+    }
+
+    let mut typesize = 0;
+    unsafe {
+        error::checked(super::with_lock(|| {
+            nc_inq_type(src, xtype, std::ptr::null_mut(), &mut typesize)
+        }))?;
+    }
+    let reclaim = needs_reclaim(src, xtype)?;
+
+    // Dimensions after `split` are copied in full, and
+    // `step` elements are copied along the dimension before
+    let budget = (buffer_size / typesize).max(1);
+    let mut split = shape.len();
+    let mut inner = 1_usize;
+    while split > 0 && inner.saturating_mul(shape[split - 1]) <= budget {
+        inner *= shape[split - 1];
+        split -= 1;
+    }
+    let step = (budget / inner).max(1);
+
+    // u64 ensures the buffer is aligned for all types
+    let outer = if split > 0 {
+        step.min(shape[split - 1])
+    } else {
+        1
+    };
+    let mut buffer = vec![0_u64; outer * inner * typesize / 8 + 1];
+    let mut start = vec![0; shape.len()];
+    let mut count = shape.clone();
+    loop {
+        for c in &mut count[..split] {
+            *c = 1;
+        }
+        if split > 0 {
+            count[split - 1] = step.min(shape[split - 1] - start[split - 1]);
+        }
+        let nelems = count.iter().product::<usize>();
+
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_get_vara(
+                    src,
+                    src_varid,
+                    start.as_ptr(),
+                    count.as_ptr(),
+                    buffer.as_mut_ptr().cast(),
+                )
+            }))?;
+        }
+        let e = unsafe {
+            super::with_lock(|| {
+                nc_put_vara(
+                    dst,
+                    dst_varid,
+                    start.as_ptr(),
+                    count.as_ptr(),
+                    buffer.as_ptr().cast(),
+                )
+            })
+        };
+        if reclaim {
+            reclaim_values(src, xtype, buffer.as_mut_ptr().cast(), nelems)?;
+        }
+        error::checked(e)?;
+
+        // Advance to the next slab, with the last dimension varying fastest
+        if split == 0 {
+            return Ok(());
+        }
+        let mut dim = split - 1;
+        start[dim] += count[dim];
+        while start[dim] >= shape[dim] {
+            start[dim] = 0;
+            if dim == 0 {
+                return Ok(());
+            }
+            dim -= 1;
+            start[dim] += 1;
+        }
+    }
+}
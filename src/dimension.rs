@@ -82,6 +82,15 @@ pub(crate) fn from_name_toid(loc: nc_type, name: &str) -> error::Result<Option<n
     Ok(Some(dimid))
 }
 
+pub(crate) fn rename(ncid: nc_type, oldname: &str, newname: &str) -> error::Result<()> {
+    let dimid = from_name_toid(ncid, oldname)?
+        .ok_or_else(|| error::Error::NotFound(format!("dimension {}", oldname)))?;
+    let cname = super::utils::short_name_to_bytes(newname)?;
+    super::utils::checked_with_redef(ncid, || unsafe {
+        nc_rename_dim(ncid, dimid, cname.as_ptr().cast())
+    })
+}
+
 pub(crate) fn from_name<'f>(loc: nc_type, name: &str) -> error::Result<Option<Dimension<'f>>> {
     let mut dimid = 0;
     let cname = super::utils::short_name_to_bytes(name)?;
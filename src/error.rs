@@ -48,6 +48,8 @@ pub enum Error {
     Utf8Conversion(std::string::FromUtf8Error),
     /// String contains NULL characters
     NulError(std::ffi::NulError),
+    /// Error when reading or writing
+    Io(std::io::Error),
 }
 
 impl Error {
@@ -107,6 +109,12 @@ impl From<std::ffi::NulError> for Error {
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
 use std::fmt;
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -143,6 +151,7 @@ impl fmt::Display for Error {
             Self::WrongDataset => write!(f, "This identifier does not belong in this dataset"),
             Self::Utf8Conversion(e) => write!(f, "{}", e),
             Self::NulError(e) => write!(f, "String value contains null bytes {}", e),
+            Self::Io(e) => e.fmt(f),
         }
     }
 }
@@ -25,15 +25,16 @@ impl Drop for RawFile {
 }
 
 #[cfg(unix)]
-fn get_ffi_from_path(path: &path::Path) -> Vec<u8> {
+fn get_ffi_from_path(path: &path::Path) -> error::Result<std::ffi::CString> {
     use std::os::unix::ffi::OsStrExt;
-    let mut bytes = path.as_os_str().as_bytes().to_vec();
-    bytes.push(0);
-    bytes
+    Ok(std::ffi::CString::new(path.as_os_str().as_bytes())?)
 }
 #[cfg(not(unix))]
-fn get_ffi_from_path(path: &path::Path) -> std::ffi::CString {
-    std::ffi::CString::new(path.to_str().unwrap()).unwrap()
+fn get_ffi_from_path(path: &path::Path) -> error::Result<std::ffi::CString> {
+    let path = path
+        .to_str()
+        .ok_or_else(|| error::Error::Str("path is not valid utf-8".into()))?;
+    Ok(std::ffi::CString::new(path)?)
 }
 
 bitflags::bitflags! {
@@ -61,10 +62,107 @@ bitflags::bitflags! {
     }
 }
 
+/// Space to reserve in classic files when leaving define mode,
+/// see [`MutableFile::define_with`]
+///
+/// Reserving space in the header avoids rewriting the
+/// whole file when attributes or variables are added later
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HeaderPadding {
+    /// Minimum free space in bytes at the end of the header
+    pub h_minfree: usize,
+    /// Alignment in bytes of the start of the fixed size variables
+    pub v_align: usize,
+    /// Minimum free space in bytes after the fixed size variables
+    pub v_minfree: usize,
+    /// Alignment in bytes of the start of the record variables
+    pub r_align: usize,
+}
+
+impl Default for HeaderPadding {
+    fn default() -> Self {
+        Self {
+            h_minfree: 0,
+            v_align: 4,
+            v_minfree: 0,
+            r_align: 4,
+        }
+    }
+}
+
+/// Whether unwritten values of variables are prefilled,
+/// see [`MutableFile::set_fill_mode`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FillMode {
+    /// Write the fill value to all values before data is written (the default)
+    Fill,
+    /// Only write the data which is explicitly written
+    NoFill,
+}
+
+/// Format of a `netCDF` file, see also [`FormatExtended`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    /// The original `netCDF` format
+    Classic,
+    /// Classic format with 64 bit offsets
+    Offset64,
+    /// Classic format with 64 bit data (`CDF-5`)
+    Cdf5,
+    /// `netCDF-4` format, stored using `HDF5`
+    Netcdf4,
+    /// `netCDF-4` format restricted to the classic data model
+    Netcdf4Classic,
+}
+
+impl Format {
+    /// Format of the file containing the group `ncid`
+    pub(crate) fn from_ncid(ncid: nc_type) -> error::Result<Self> {
+        let mut format = 0;
+        unsafe {
+            error::checked(super::with_lock(|| nc_inq_format(ncid, &mut format)))?;
+        }
+
+        match format {
+            NC_FORMAT_CLASSIC => Ok(Self::Classic),
+            NC_FORMAT_64BIT_OFFSET => Ok(Self::Offset64),
+            NC_FORMAT_CDF5 => Ok(Self::Cdf5),
+            NC_FORMAT_NETCDF4 => Ok(Self::Netcdf4),
+            NC_FORMAT_NETCDF4_CLASSIC => Ok(Self::Netcdf4Classic),
+            _ => Err(NC_EFILEMETA.into()),
+        }
+    }
+}
+
+/// The underlying storage or protocol used to access a file
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FormatExtended {
+    /// Classic formats
+    Nc3,
+    /// `HDF5` files, which includes `netCDF-4`
+    Hdf5,
+    /// `HDF4` files
+    Hdf4,
+    /// Classic formats accessed through `PnetCDF`
+    PNetcdf,
+    /// Remote access through `DAP2`
+    Dap2,
+    /// Remote access through `DAP4`
+    Dap4,
+    /// First user defined format
+    Udf0,
+    /// Second user defined format
+    Udf1,
+    /// `Zarr` storage
+    NcZarr,
+    /// Unknown format
+    Undefined,
+}
+
 impl RawFile {
     /// Open a `netCDF` file in read only mode.
     pub(crate) fn open_with(path: &path::Path, options: Options) -> error::Result<File> {
-        let f = get_ffi_from_path(path);
+        let f = get_ffi_from_path(path)?;
         let mut ncid: nc_type = 0;
         unsafe {
             error::checked(super::with_lock(|| {
@@ -82,7 +180,7 @@ impl RawFile {
 
     /// Create a new `netCDF` file
     pub(crate) fn create_with(path: &path::Path, options: Options) -> error::Result<MutableFile> {
-        let f = get_ffi_from_path(path);
+        let f = get_ffi_from_path(path)?;
         let mut ncid: nc_type = -1;
         unsafe {
             error::checked(super::with_lock(|| {
@@ -98,7 +196,7 @@ impl RawFile {
         name: Option<&str>,
         mem: &'buffer [u8],
     ) -> error::Result<MemFile<'buffer>> {
-        let cstr = std::ffi::CString::new(name.unwrap_or("/")).unwrap();
+        let cstr = std::ffi::CString::new(name.unwrap_or("/"))?;
         let mut ncid = 0;
         unsafe {
             error::checked(super::with_lock(|| {
@@ -114,6 +212,27 @@ impl RawFile {
 
         Ok(MemFile(File(Self { ncid }), PhantomData))
     }
+
+    #[cfg(feature = "memory")]
+    pub(crate) fn create_in_memory(
+        name: Option<&str>,
+        options: Options,
+    ) -> error::Result<MemMutableFile> {
+        let cstr = std::ffi::CString::new(name.unwrap_or("/"))?;
+        let mut ncid = 0;
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_create_mem(
+                    cstr.as_ptr(),
+                    options.bits(),
+                    NC_SIZEHINT_DEFAULT as _,
+                    &mut ncid,
+                )
+            }))?;
+        }
+
+        Ok(MemMutableFile(MutableFile(File(Self { ncid }))))
+    }
 }
 
 #[derive(Debug)]
@@ -158,22 +277,66 @@ impl File {
         }
     }
 
+    /// Write the contents of the file as `CDL`, in the format used
+    /// by `ncdump`. The dataset is named after the file path,
+    /// without directories and extension
+    ///
+    /// # Errors
+    ///
+    /// Errors from the netcdf layer or from writing to `writer`
+    pub fn to_cdl<W: std::io::Write>(
+        &self,
+        mut writer: W,
+        options: &super::cdl::CdlOptions,
+    ) -> error::Result<()> {
+        let path = self.path()?;
+        let name = path
+            .file_stem()
+            .map_or_else(|| "unknown".into(), |name| name.to_string_lossy());
+        super::cdl::write_file(self, &name, &mut writer, options)
+    }
+
     /// Main entrypoint for interacting with the netcdf file.
     pub fn root(&self) -> Option<Group> {
+        if matches!(self.format(), Ok(Format::Netcdf4 | Format::Netcdf4Classic)) {
+            Some(Group {
+                ncid: self.ncid(),
+                _file: PhantomData,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Format of the file
+    pub fn format(&self) -> error::Result<Format> {
+        Format::from_ncid(self.ncid())
+    }
+
+    /// The underlying storage or protocol used for the file
+    pub fn format_extended(&self) -> error::Result<FormatExtended> {
         let mut format = 0;
-        unsafe { error::checked(super::with_lock(|| nc_inq_format(self.ncid(), &mut format))) }
-            .unwrap();
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_inq_format_extended(self.ncid(), &mut format, std::ptr::null_mut())
+            }))?;
+        }
 
         match format {
-            NC_FORMAT_NETCDF4 | NC_FORMAT_NETCDF4_CLASSIC => Some(Group {
-                ncid: self.ncid(),
-                _file: PhantomData,
-            }),
-            _ => None,
+            NC_FORMATX_NC3 => Ok(FormatExtended::Nc3),
+            NC_FORMATX_NC_HDF5 => Ok(FormatExtended::Hdf5),
+            NC_FORMATX_NC_HDF4 => Ok(FormatExtended::Hdf4),
+            NC_FORMATX_PNETCDF => Ok(FormatExtended::PNetcdf),
+            NC_FORMATX_DAP2 => Ok(FormatExtended::Dap2),
+            NC_FORMATX_DAP4 => Ok(FormatExtended::Dap4),
+            NC_FORMATX_UDF0 => Ok(FormatExtended::Udf0),
+            NC_FORMATX_UDF1 => Ok(FormatExtended::Udf1),
+            NC_FORMATX_NCZARR => Ok(FormatExtended::NcZarr),
+            _ => Ok(FormatExtended::Undefined),
         }
     }
 
-    fn ncid(&self) -> nc_type {
+    pub(crate) fn ncid(&self) -> nc_type {
         self.0.ncid
     }
 
@@ -230,6 +393,27 @@ impl File {
     pub fn types(&self) -> error::Result<impl Iterator<Item = super::types::VariableType>> {
         super::types::all_at_location(self.ncid()).map(|x| x.map(Result::unwrap))
     }
+    /// Check whether the filter with the given identifier
+    /// is available for use with this file
+    #[cfg(feature = "4.9.0")]
+    pub fn filter_available(&self, id: u32) -> error::Result<bool> {
+        let e = unsafe { super::with_lock(|| nc_inq_filter_avail(self.ncid(), id)) };
+        if e == NC_ENOFILTER {
+            return Ok(false);
+        }
+        error::checked(e)?;
+        Ok(true)
+    }
+    /// Check whether the filter with the given identifier
+    /// is available for use with this file
+    ///
+    /// Versions of `netCDF` prior to `4.9.0` can not be queried
+    /// for filters, and all filters are reported as unavailable
+    #[cfg(not(feature = "4.9.0"))]
+    #[allow(clippy::unused_self)]
+    pub fn filter_available(&self, _id: u32) -> error::Result<bool> {
+        Ok(false)
+    }
 }
 
 /// Mutable access to file
@@ -298,6 +482,35 @@ impl MutableFile {
         Attribute::put(self.ncid(), NC_GLOBAL, name, val.into())
     }
 
+    /// Rename an attribute of the root group
+    ///
+    /// # Errors
+    ///
+    /// [`error::Error::NotFound`] if the attribute does not exist, or
+    /// [`error::Error::AlreadyExists`] if the new name is in use
+    pub fn rename_attribute(&mut self, oldname: &str, newname: &str) -> error::Result<()> {
+        Attribute::rename(self.ncid(), None, oldname, newname)
+    }
+
+    /// Remove an attribute from the root group
+    ///
+    /// # Errors
+    ///
+    /// [`error::Error::NotFound`] if the attribute does not exist
+    pub fn remove_attribute(&mut self, name: &str) -> error::Result<()> {
+        Attribute::remove(self.ncid(), None, name)
+    }
+
+    /// Rename a dimension of the root group
+    ///
+    /// # Errors
+    ///
+    /// [`error::Error::NotFound`] if the dimension does not exist, or
+    /// [`error::Error::AlreadyExists`] if the new name is in use
+    pub fn rename_dimension(&mut self, oldname: &str, newname: &str) -> error::Result<()> {
+        super::dimension::rename(self.ncid(), oldname, newname)
+    }
+
     /// Adds a dimension with the given name and size. A size of zero gives an unlimited dimension
     pub fn add_dimension<'f>(&'f mut self, name: &str, len: usize) -> error::Result<Dimension<'f>> {
         super::dimension::add_dimension_at(self.ncid(), name, len)
@@ -369,6 +582,14 @@ impl MutableFile {
         super::types::CompoundType::add(self.ncid(), name)
     }
 
+    /// Define the compound type corresponding to a rust struct,
+    /// as given by [`NcCompound`](super::types::NcCompound)
+    pub fn add_compound_type_from<T: super::types::NcCompound>(
+        &mut self,
+    ) -> error::Result<super::types::CompoundType> {
+        super::types::CompoundType::add_from::<T>(self.ncid())
+    }
+
     /// Adds a variable with a basic type of string
     pub fn add_string_variable<'f>(
         &'f mut self,
@@ -389,6 +610,129 @@ impl MutableFile {
     {
         super::variable::add_variable_from_identifiers(self.ncid(), name, dims, T::NCTYPE)
     }
+
+    /// Define dimensions, variables, and attributes in a single
+    /// pass of define mode, avoiding repeated rewrites of the
+    /// header of classic files
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut file = netcdf::create_with("file.nc", netcdf::Options::CLASSIC)?;
+    /// file.define(|file| {
+    ///     file.add_dimension("x", 10)?;
+    ///     for i in 0..100 {
+    ///         file.add_variable::<f32>(&format!("var{}", i), &["x"])?;
+    ///     }
+    ///     Ok(())
+    /// })?;
+    /// file.variable_mut("var0").unwrap().put_values(&[1.0; 10], None, None)?;
+    /// # Ok(()) }
+    /// ```
+    pub fn define<F, T>(&mut self, f: F) -> error::Result<T>
+    where
+        F: FnOnce(&mut Self) -> error::Result<T>,
+    {
+        self.define_with(HeaderPadding::default(), f)
+    }
+
+    /// Define metadata as in [`define`](Self::define), reserving
+    /// space in the header as given by `padding` when leaving
+    /// define mode. The padding only has an effect on classic files
+    pub fn define_with<F, T>(&mut self, padding: HeaderPadding, f: F) -> error::Result<T>
+    where
+        F: FnOnce(&mut Self) -> error::Result<T>,
+    {
+        let e = unsafe { super::with_lock(|| nc_redef(self.ncid())) };
+        // A newly created file is already in define mode
+        if e != NC_EINDEFINE {
+            error::checked(e)?;
+        }
+
+        let result = f(self);
+
+        let e = unsafe {
+            super::with_lock(|| {
+                nc__enddef(
+                    self.ncid(),
+                    padding.h_minfree,
+                    padding.v_align,
+                    padding.v_minfree,
+                    padding.r_align,
+                )
+            })
+        };
+        // Errors from the closure are more informative
+        let value = result?;
+        error::checked(e)?;
+        Ok(value)
+    }
+
+    /// Set whether variables in the file are prefilled with the fill value,
+    /// returning the previous mode. Turning off prefilling speeds up
+    /// writing when all values are written anyway
+    ///
+    /// # Errors
+    ///
+    /// The mode could not be set
+    ///
+    /// # Safety
+    ///
+    /// Reading from variables after having set [`FillMode::NoFill`]
+    /// will read potentially uninitialized data. Normally
+    /// one will expect to find some filler value
+    pub unsafe fn set_fill_mode(&mut self, mode: FillMode) -> error::Result<FillMode> {
+        let fillmode = match mode {
+            FillMode::Fill => NC_FILL,
+            FillMode::NoFill => NC_NOFILL,
+        };
+        let mut old_mode = 0;
+        error::checked(super::with_lock(|| {
+            nc_set_fill(self.ncid(), fillmode, &mut old_mode)
+        }))?;
+        match old_mode {
+            NC_FILL => Ok(FillMode::Fill),
+            NC_NOFILL => Ok(FillMode::NoFill),
+            _ => Err(format!("unknown fill mode {}", old_mode).into()),
+        }
+    }
+
+    /// Flush the contents of the file to disk
+    ///
+    /// # Errors
+    ///
+    /// The file could not be written
+    pub fn sync(&mut self) -> error::Result<()> {
+        unsafe { error::checked(super::with_lock(|| nc_sync(self.ncid()))) }
+    }
+
+    /// Close the file, reporting errors which would
+    /// otherwise be ignored when the file is dropped
+    ///
+    /// # Errors
+    ///
+    /// The file could not be written
+    pub fn close(self) -> error::Result<()> {
+        let ncid = self.ncid();
+        // The file is closed below, must not be closed again on drop
+        std::mem::forget(self);
+
+        unsafe { error::checked(super::with_lock(|| nc_close(ncid))) }
+    }
+
+    /// Close the file, discarding changes to the metadata since the
+    /// file last left define mode. A newly created file is deleted
+    ///
+    /// # Errors
+    ///
+    /// The file could not be closed
+    pub fn abort(self) -> error::Result<()> {
+        let ncid = self.ncid();
+        // The file is closed below, must not be closed again on drop
+        std::mem::forget(self);
+
+        unsafe { error::checked(super::with_lock(|| nc_abort(ncid))) }
+    }
 }
 
 #[cfg(feature = "memory")]
@@ -414,3 +758,69 @@ impl<'a> std::ops::Deref for MemFile<'a> {
         &self.0
     }
 }
+
+#[cfg(feature = "memory")]
+/// A writable file which only lives in memory. The contents
+/// of the file is returned as a buffer when closing the file.
+///
+/// Access a [`MutableFile`] through the `Deref` and `DerefMut` traits,
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut file = netcdf::create_mem(None)?;
+/// file.add_dimension("x", 10)?;
+///
+/// let buffer: Vec<u8> = file.close()?;
+/// # Ok(()) }
+/// ```
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct MemMutableFile(MutableFile);
+
+#[cfg(feature = "memory")]
+impl std::ops::Deref for MemMutableFile {
+    type Target = MutableFile;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(feature = "memory")]
+impl std::ops::DerefMut for MemMutableFile {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[cfg(feature = "memory")]
+impl MemMutableFile {
+    /// Close the file and return the finished `netCDF` file as bytes
+    ///
+    /// # Errors
+    ///
+    /// The file could not be written to the memory buffer
+    pub fn close(self) -> error::Result<Vec<u8>> {
+        let ncid = self.ncid();
+        // The file is closed below, must not be closed again on drop
+        std::mem::forget(self);
+
+        let mut memio = NC_memio {
+            size: 0,
+            memory: std::ptr::null_mut(),
+            flags: 0,
+        };
+        unsafe {
+            error::checked(super::with_lock(|| nc_close_memio(ncid, &mut memio)))?;
+        }
+        if memio.memory.is_null() {
+            return Ok(Vec::new());
+        }
+        // The buffer is allocated by `netCDF` and must be released by us
+        let buffer =
+            unsafe { std::slice::from_raw_parts(memio.memory as *const u8, memio.size) }.to_vec();
+        unsafe {
+            libc::free(memio.memory);
+        }
+
+        Ok(buffer)
+    }
+}
@@ -189,6 +189,14 @@ impl<'f> GroupMut<'f> {
         super::types::CompoundType::add(self.id(), name)
     }
 
+    /// Define the compound type corresponding to a rust struct,
+    /// as given by [`NcCompound`](super::types::NcCompound)
+    pub fn add_compound_type_from<T: super::types::NcCompound>(
+        &mut self,
+    ) -> error::Result<super::types::CompoundType> {
+        super::types::CompoundType::add_from::<T>(self.id())
+    }
+
     /// Add an attribute to the group
     pub fn add_attribute<'a, T>(&'a mut self, name: &str, val: T) -> error::Result<Attribute<'a>>
     where
@@ -197,6 +205,47 @@ impl<'f> GroupMut<'f> {
         Attribute::put(self.ncid, NC_GLOBAL, name, val.into())
     }
 
+    /// Rename the group
+    ///
+    /// # Errors
+    ///
+    /// [`error::Error::AlreadyExists`] if the name is in use
+    pub fn rename(&mut self, name: &str) -> error::Result<()> {
+        let cname = super::utils::short_name_to_bytes(name)?;
+        super::utils::checked_with_redef(self.ncid, || unsafe {
+            nc_rename_grp(self.ncid, cname.as_ptr().cast())
+        })
+    }
+
+    /// Rename an attribute of the group
+    ///
+    /// # Errors
+    ///
+    /// [`error::Error::NotFound`] if the attribute does not exist, or
+    /// [`error::Error::AlreadyExists`] if the new name is in use
+    pub fn rename_attribute(&mut self, oldname: &str, newname: &str) -> error::Result<()> {
+        Attribute::rename(self.ncid, None, oldname, newname)
+    }
+
+    /// Remove an attribute from the group
+    ///
+    /// # Errors
+    ///
+    /// [`error::Error::NotFound`] if the attribute does not exist
+    pub fn remove_attribute(&mut self, name: &str) -> error::Result<()> {
+        Attribute::remove(self.ncid, None, name)
+    }
+
+    /// Rename a dimension visible from the group
+    ///
+    /// # Errors
+    ///
+    /// [`error::Error::NotFound`] if the dimension does not exist, or
+    /// [`error::Error::AlreadyExists`] if the new name is in use
+    pub fn rename_dimension(&mut self, oldname: &str, newname: &str) -> error::Result<()> {
+        super::dimension::rename(self.ncid, oldname, newname)
+    }
+
     /// Adds a dimension with the given name and size. A size of zero gives an unlimited dimension
     pub fn add_dimension<'g>(&'g mut self, name: &str, len: usize) -> error::Result<Dimension<'g>> {
         super::dimension::add_dimension_at(self.id(), name, len)
@@ -89,6 +89,8 @@ use netcdf_sys::nc_type;
 use std::sync::Mutex;
 
 pub mod attribute;
+pub mod cdl;
+pub mod copy;
 pub mod dimension;
 pub mod error;
 pub mod file;
@@ -102,6 +104,9 @@ pub use file::*;
 pub use group::*;
 pub use variable::*;
 
+#[cfg(feature = "derive")]
+pub use netcdf_derive::NcCompound;
+
 /// Open a netcdf file in create mode
 ///
 /// Will create a `netCDF4` file and overwrite existing file
@@ -158,6 +163,47 @@ pub fn open_mem<'a>(name: Option<&str>, mem: &'a [u8]) -> error::Result<MemFile<
     RawFile::open_from_memory(name, mem)
 }
 
+#[cfg(feature = "memory")]
+/// Create a `netCDF4` file which only lives in memory
+pub fn create_mem(name: Option<&str>) -> error::Result<MemMutableFile> {
+    create_mem_with(name, Options::NETCDF4)
+}
+
+#[cfg(feature = "memory")]
+/// Create a `netCDF` file which only lives in memory, with the given options
+pub fn create_mem_with(name: Option<&str>, options: Options) -> error::Result<MemMutableFile> {
+    RawFile::create_in_memory(name, options)
+}
+
+/// Set the default chunk cache used for variables in files
+/// opened or created after this call
+pub fn set_chunk_cache(cache: ChunkCache) -> error::Result<()> {
+    unsafe {
+        error::checked(with_lock(|| {
+            netcdf_sys::nc_set_chunk_cache(cache.size, cache.nelems, cache.preemption)
+        }))
+    }
+}
+
+/// Get the default chunk cache used for variables in new files
+pub fn chunk_cache() -> error::Result<ChunkCache> {
+    let mut cache = ChunkCache {
+        size: 0,
+        nelems: 0,
+        preemption: 0.0,
+    };
+    unsafe {
+        error::checked(with_lock(|| {
+            netcdf_sys::nc_get_chunk_cache(
+                &mut cache.size,
+                &mut cache.nelems,
+                &mut cache.preemption,
+            )
+        }))?;
+    }
+    Ok(cache)
+}
+
 lazy_static! {
     /// Use this when accessing `netCDF` functions
     pub(crate) static ref LOCK: Mutex<()> = Mutex::new(());
@@ -172,7 +218,8 @@ pub(crate) fn with_lock<F: FnMut() -> nc_type>(mut f: F) -> nc_type {
 
 pub(crate) mod utils {
     use super::error;
-    use netcdf_sys::{NC_EMAXNAME, NC_MAX_NAME};
+    use netcdf_sys::*;
+    use std::convert::TryInto;
     /// Use this function for short `netCDF` names to avoid the allocation
     /// for a `CString`
     pub(crate) fn short_name_to_bytes(name: &str) -> error::Result<[u8; NC_MAX_NAME as usize + 1]> {
@@ -185,4 +232,141 @@ pub(crate) mod utils {
             Ok(bytes)
         }
     }
+
+    /// Calls `f`, entering and leaving define mode if required
+    /// by the operation. This is only necessary for classic files,
+    /// `netCDF-4` files switches mode automatically
+    pub(crate) fn checked_with_redef<F>(ncid: nc_type, mut f: F) -> error::Result<()>
+    where
+        F: FnMut() -> nc_type,
+    {
+        let e = super::with_lock(&mut f);
+        if e != NC_ENOTINDEFINE {
+            return error::checked(e);
+        }
+        unsafe {
+            error::checked(super::with_lock(|| nc_redef(ncid)))?;
+        }
+        let e = super::with_lock(&mut f);
+        let e_enddef = unsafe { super::with_lock(|| nc_enddef(ncid)) };
+        error::checked(e)?;
+        error::checked(e_enddef)
+    }
+    /// Enters define mode, unless already in define mode
+    pub(crate) fn redef(ncid: nc_type) -> error::Result<()> {
+        let e = unsafe { super::with_lock(|| nc_redef(ncid)) };
+        if e == NC_EINDEFINE {
+            return Ok(());
+        }
+        error::checked(e)
+    }
+
+    /// Leaves define mode, unless already in data mode
+    pub(crate) fn enddef(ncid: nc_type) -> error::Result<()> {
+        let e = unsafe { super::with_lock(|| nc_enddef(ncid)) };
+        if e == NC_ENOTINDEFINE {
+            return Ok(());
+        }
+        error::checked(e)
+    }
+
+    /// The part of a name buffer filled in by `netCDF` up to the terminating zero
+    pub(crate) fn name_to_str(name: &[u8]) -> error::Result<&str> {
+        let zeropos = name.iter().position(|&x| x == 0).unwrap_or(name.len());
+        std::str::from_utf8(&name[..zeropos])
+            .map_err(|_| error::Error::Str("name is not valid utf-8".into()))
+    }
+
+    /// Whether values of the type holds memory allocated by `netCDF`
+    pub(crate) fn needs_reclaim(ncid: nc_type, xtype: nc_type) -> error::Result<bool> {
+        if xtype == NC_STRING {
+            return Ok(true);
+        }
+        if xtype <= NC_MAX_ATOMIC_TYPE {
+            return Ok(false);
+        }
+        let mut nfields = 0;
+        let mut class = 0;
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_inq_user_type(
+                    ncid,
+                    xtype,
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                    &mut nfields,
+                    &mut class,
+                )
+            }))?;
+        }
+        match class {
+            NC_VLEN => Ok(true),
+            NC_COMPOUND => {
+                for fieldid in 0..nfields.try_into()? {
+                    let mut fieldtype = 0;
+                    unsafe {
+                        error::checked(super::with_lock(|| {
+                            nc_inq_compound_fieldtype(ncid, xtype, fieldid, &mut fieldtype)
+                        }))?;
+                    }
+                    if needs_reclaim(ncid, fieldtype)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    #[cfg(feature = "4.8.0")]
+    pub(crate) fn reclaim_values(
+        ncid: nc_type,
+        xtype: nc_type,
+        values: *mut std::os::raw::c_void,
+        nelems: usize,
+    ) -> error::Result<()> {
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_reclaim_data(ncid, xtype, values, nelems)
+            }))
+        }
+    }
+
+    /// Older versions of `netCDF` can only free the top level
+    /// of strings and variable length arrays, nested data is leaked
+    #[cfg(not(feature = "4.8.0"))]
+    pub(crate) fn reclaim_values(
+        ncid: nc_type,
+        xtype: nc_type,
+        values: *mut std::os::raw::c_void,
+        nelems: usize,
+    ) -> error::Result<()> {
+        if xtype == NC_STRING {
+            return unsafe {
+                error::checked(super::with_lock(|| nc_free_string(nelems, values.cast())))
+            };
+        }
+        let mut class = 0;
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_inq_user_type(
+                    ncid,
+                    xtype,
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                    &mut class,
+                )
+            }))?;
+        }
+        if class == NC_VLEN {
+            return unsafe {
+                error::checked(super::with_lock(|| nc_free_vlens(nelems, values.cast())))
+            };
+        }
+        Ok(())
+    }
 }
@@ -34,7 +34,7 @@ pub enum BasicType {
 
 impl BasicType {
     /// Size of the type in bytes
-    fn size(self) -> usize {
+    pub(crate) fn size(self) -> usize {
         match self {
             Self::Byte | Self::Ubyte | Self::Char => 1,
             Self::Short | Self::Ushort => 2,
@@ -60,6 +60,81 @@ impl BasicType {
         }
     }
 
+    /// Reads an integer of this type, `None` for non-integer types
+    #[allow(clippy::cast_possible_wrap)]
+    pub(crate) fn integer_from_ne_bytes(self, bytes: &[u8]) -> Option<i64> {
+        let value = match self {
+            Self::Byte => i8::from_ne_bytes(bytes.try_into().ok()?).into(),
+            Self::Ubyte => u8::from_ne_bytes(bytes.try_into().ok()?).into(),
+            Self::Short => i16::from_ne_bytes(bytes.try_into().ok()?).into(),
+            Self::Ushort => u16::from_ne_bytes(bytes.try_into().ok()?).into(),
+            Self::Int => i32::from_ne_bytes(bytes.try_into().ok()?).into(),
+            Self::Uint => u32::from_ne_bytes(bytes.try_into().ok()?).into(),
+            Self::Int64 => i64::from_ne_bytes(bytes.try_into().ok()?),
+            Self::Uint64 => u64::from_ne_bytes(bytes.try_into().ok()?) as i64,
+            Self::Char | Self::Float | Self::Double => return None,
+        };
+        Some(value)
+    }
+
+    /// Writes an integer as this type, `None` for non-integer types
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub(crate) fn integer_to_ne_bytes(self, value: i64) -> Option<Vec<u8>> {
+        let bytes = match self {
+            Self::Byte => (value as i8).to_ne_bytes().to_vec(),
+            Self::Ubyte => (value as u8).to_ne_bytes().to_vec(),
+            Self::Short => (value as i16).to_ne_bytes().to_vec(),
+            Self::Ushort => (value as u16).to_ne_bytes().to_vec(),
+            Self::Int => (value as i32).to_ne_bytes().to_vec(),
+            Self::Uint => (value as u32).to_ne_bytes().to_vec(),
+            Self::Int64 => value.to_ne_bytes().to_vec(),
+            Self::Uint64 => (value as u64).to_ne_bytes().to_vec(),
+            Self::Char | Self::Float | Self::Double => return None,
+        };
+        Some(bytes)
+    }
+
+    /// Default fill value of the type, used when `_FillValue` is not set
+    #[allow(clippy::cast_precision_loss)]
+    pub(crate) fn default_fill(self) -> f64 {
+        use super::Numeric;
+        match self {
+            Self::Byte => i8::DEFAULT_FILL.into(),
+            Self::Char => f64::from(NC_FILL_CHAR as u8),
+            Self::Ubyte => u8::DEFAULT_FILL.into(),
+            Self::Short => i16::DEFAULT_FILL.into(),
+            Self::Ushort => u16::DEFAULT_FILL.into(),
+            Self::Int => i32::DEFAULT_FILL.into(),
+            Self::Uint => u32::DEFAULT_FILL.into(),
+            Self::Int64 => i64::DEFAULT_FILL as f64,
+            Self::Uint64 => u64::DEFAULT_FILL as f64,
+            Self::Float => f32::DEFAULT_FILL.into(),
+            Self::Double => f64::DEFAULT_FILL,
+        }
+    }
+
+    /// Range of values of an integer type, `None` for non-integer types
+    #[allow(clippy::cast_precision_loss)]
+    pub(crate) fn integer_range(self) -> Option<(f64, f64)> {
+        let range = match self {
+            Self::Byte => (i8::MIN.into(), i8::MAX.into()),
+            Self::Ubyte => (u8::MIN.into(), u8::MAX.into()),
+            Self::Short => (i16::MIN.into(), i16::MAX.into()),
+            Self::Ushort => (u16::MIN.into(), u16::MAX.into()),
+            Self::Int => (i32::MIN.into(), i32::MAX.into()),
+            Self::Uint => (u32::MIN.into(), u32::MAX.into()),
+            // The maximum values round up to a power of two, which
+            // overflows, use the next smaller double instead
+            Self::Int64 => (
+                i64::MIN as f64,
+                f64::from_bits((i64::MAX as f64).to_bits() - 1),
+            ),
+            Self::Uint64 => (0.0, f64::from_bits((u64::MAX as f64).to_bits() - 1)),
+            Self::Char | Self::Float | Self::Double => return None,
+        };
+        Some(range)
+    }
+
     /// `rusty` name of the type
     pub fn name(self) -> &'static str {
         match self {
@@ -352,6 +427,24 @@ impl EnumType {
         String::from_utf8(name[..pos].to_vec()).unwrap()
     }
 
+    /// Get all members of the enum, with values converted to `i64`
+    /// for all integer base types
+    pub(crate) fn members_i64(&self) -> error::Result<Vec<(String, i64)>> {
+        #[allow(clippy::cast_possible_wrap)]
+        let members = match self.typ() {
+            BasicType::Byte => self.members::<i8>()?.map(|(n, v)| (n, v.into())).collect(),
+            BasicType::Ubyte => self.members::<u8>()?.map(|(n, v)| (n, v.into())).collect(),
+            BasicType::Short => self.members::<i16>()?.map(|(n, v)| (n, v.into())).collect(),
+            BasicType::Ushort => self.members::<u16>()?.map(|(n, v)| (n, v.into())).collect(),
+            BasicType::Int => self.members::<i32>()?.map(|(n, v)| (n, v.into())).collect(),
+            BasicType::Uint => self.members::<u32>()?.map(|(n, v)| (n, v.into())).collect(),
+            BasicType::Int64 => self.members::<i64>()?.collect(),
+            BasicType::Uint64 => self.members::<u64>()?.map(|(n, v)| (n, v as i64)).collect(),
+            typ => return Err(error::Error::TypeUnknown(typ.id())),
+        };
+        Ok(members)
+    }
+
     /// Get the name from the enum value
     pub fn name_from_value(&self, value: i64) -> Option<String> {
         let mut name = [0_u8; NC_MAX_NAME as usize + 1];
@@ -427,6 +520,84 @@ impl CompoundType {
         String::from_utf8(name[..pos].to_vec()).unwrap()
     }
 
+    /// Define the compound type corresponding to a rust type. Nested
+    /// compounds are reused if a type of the same name and layout
+    /// already exists in the group, and defined otherwise
+    pub(crate) fn add_from<T: NcCompound>(ncid: nc_type) -> error::Result<Self> {
+        Self::define(ncid, &T::compound_descriptor())
+    }
+
+    fn define(ncid: nc_type, descriptor: &CompoundDescriptor) -> error::Result<Self> {
+        let mut builder = Self::add(ncid, &descriptor.name)?;
+        builder.size = descriptor.size;
+        for field in &descriptor.fields {
+            let typ = match &field.typ {
+                NcTypeDescriptor::Basic(basic) => VariableType::Basic(*basic),
+                NcTypeDescriptor::Compound(nested) => {
+                    let existing = Self::find(ncid, &nested.name)?;
+                    match existing {
+                        Some(existing) => {
+                            existing.check_layout(nested)?;
+                            existing.into()
+                        }
+                        None => Self::define(ncid, nested)?.into(),
+                    }
+                }
+            };
+            builder.add_field_at(&field.name, &typ, &field.dims, field.offset)?;
+        }
+        builder.build()
+    }
+
+    /// Find a compound type by name in the group
+    fn find(ncid: nc_type, name: &str) -> error::Result<Option<Self>> {
+        let cname = super::utils::short_name_to_bytes(name)?;
+        let mut id = 0;
+        let err =
+            super::with_lock(|| unsafe { nc_inq_typeid(ncid, cname.as_ptr().cast(), &mut id) });
+        if err == NC_EBADTYPE {
+            return Ok(None);
+        }
+        error::checked(err)?;
+        match VariableType::from_id(ncid, id)? {
+            VariableType::Compound(c) => Ok(Some(c)),
+            _ => Err(error::Error::AlreadyExists),
+        }
+    }
+
+    /// Checks that the fields of this type have the names, offsets,
+    /// types and dimensions given by the descriptor
+    ///
+    /// # Errors
+    ///
+    /// [`error::Error::TypeMismatch`] if the layout differs
+    pub fn check_layout(&self, descriptor: &CompoundDescriptor) -> error::Result<()> {
+        if self.size() != descriptor.size {
+            return Err(error::Error::TypeMismatch);
+        }
+        let fields = self.fields().collect::<Vec<_>>();
+        if fields.len() != descriptor.fields.len() {
+            return Err(error::Error::TypeMismatch);
+        }
+        for (field, expected) in fields.iter().zip(&descriptor.fields) {
+            if field.name() != expected.name
+                || field.offset() != expected.offset
+                || field.dimensions().unwrap_or_default() != expected.dims
+            {
+                return Err(error::Error::TypeMismatch);
+            }
+            match (field.typ(), &expected.typ) {
+                (VariableType::Basic(basic), NcTypeDescriptor::Basic(expected))
+                    if basic == *expected => {}
+                (VariableType::Compound(nested), NcTypeDescriptor::Compound(expected)) => {
+                    nested.check_layout(expected)?;
+                }
+                _ => return Err(error::Error::TypeMismatch),
+            }
+        }
+        Ok(())
+    }
+
     /// Get the fields of the compound
     pub fn fields(&self) -> impl Iterator<Item = CompoundField> {
         let ncid = self.ncid;
@@ -523,24 +694,109 @@ impl CompoundField {
     }
 }
 
+/// Memory layout of a rust type usable as a field in a compound type
+#[derive(Debug, Clone, PartialEq)]
+pub enum NcTypeDescriptor {
+    /// A basic numeric type
+    Basic(BasicType),
+    /// A nested compound type
+    Compound(CompoundDescriptor),
+}
+
+/// Memory layout of a rust type corresponding to a compound type
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompoundDescriptor {
+    /// Name of the compound type
+    pub name: String,
+    /// Size of the rust type in bytes
+    pub size: usize,
+    /// Fields of the type, in the order of declaration
+    pub fields: Vec<FieldDescriptor>,
+}
+
+/// Memory layout of a field of a compound type
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDescriptor {
+    /// Name of the field
+    pub name: String,
+    /// Offset of the field in bytes
+    pub offset: usize,
+    /// Type of the field (or of the elements of an array field)
+    pub typ: NcTypeDescriptor,
+    /// Dimensions of an array field, empty for a scalar field
+    pub dims: Vec<usize>,
+}
+
+/// A rust type which can be a field in a compound type
+///
+/// # Safety
+///
+/// The descriptor must describe the memory layout of the type,
+/// and any bit pattern read from a file must be valid for the type
+pub unsafe trait NcType: Copy {
+    /// Memory layout of the type
+    fn type_descriptor() -> NcTypeDescriptor;
+}
+
+macro_rules! impl_nctype {
+    ($sized_type: ty, $basic: ident) => {
+        unsafe impl NcType for $sized_type {
+            fn type_descriptor() -> NcTypeDescriptor {
+                NcTypeDescriptor::Basic(BasicType::$basic)
+            }
+        }
+    };
+}
+
+impl_nctype!(i8, Byte);
+impl_nctype!(u8, Ubyte);
+impl_nctype!(i16, Short);
+impl_nctype!(u16, Ushort);
+impl_nctype!(i32, Int);
+impl_nctype!(u32, Uint);
+impl_nctype!(i64, Int64);
+impl_nctype!(u64, Uint64);
+impl_nctype!(f32, Float);
+impl_nctype!(f64, Double);
+
+/// A rust struct corresponding to a compound type, usually
+/// implemented with `#[derive(NcCompound)]` (requires the `derive` feature)
+///
+/// # Safety
+///
+/// The descriptor must describe the memory layout of the type,
+/// and any bit pattern read from a file must be valid for the type
+pub unsafe trait NcCompound: NcType {
+    /// Memory layout of the type
+    fn compound_descriptor() -> CompoundDescriptor;
+}
+
 /// A builder for a compound type
 #[must_use]
 pub struct CompoundBuilder {
     ncid: nc_type,
     name: [u8; NC_MAX_NAME as usize + 1],
     size: usize,
-    comp: Vec<(
-        VariableType,
-        [u8; NC_MAX_NAME as usize + 1],
-        Option<Vec<i32>>,
-    )>,
+    comp: Vec<BuilderField>,
+}
+
+/// A field to be inserted in a compound type
+struct BuilderField {
+    typ: VariableType,
+    name: [u8; NC_MAX_NAME as usize + 1],
+    dims: Option<Vec<i32>>,
+    offset: usize,
 }
 
 impl CompoundBuilder {
     /// Add a type to the compound
     pub fn add_type(&mut self, name: &str, var: &VariableType) -> error::Result<&mut Self> {
-        self.comp
-            .push((var.clone(), super::utils::short_name_to_bytes(name)?, None));
+        self.comp.push(BuilderField {
+            typ: var.clone(),
+            name: super::utils::short_name_to_bytes(name)?,
+            dims: None,
+            offset: self.size,
+        });
 
         self.size += var.size();
         Ok(self)
@@ -569,16 +825,43 @@ impl CompoundBuilder {
         var: &VariableType,
         dims: &[usize],
     ) -> error::Result<&mut Self> {
-        self.comp.push((
-            var.clone(),
-            super::utils::short_name_to_bytes(name)?,
-            Some(dims.iter().map(|&x| x.try_into().unwrap()).collect()),
-        ));
+        self.comp.push(BuilderField {
+            typ: var.clone(),
+            name: super::utils::short_name_to_bytes(name)?,
+            dims: Some(dims.iter().map(|&x| x.try_into().unwrap()).collect()),
+            offset: self.size,
+        });
 
         self.size += var.size() * dims.iter().product::<usize>();
         Ok(self)
     }
 
+    /// Add a field at an explicit offset, growing the compound to fit
+    fn add_field_at(
+        &mut self,
+        name: &str,
+        var: &VariableType,
+        dims: &[usize],
+        offset: usize,
+    ) -> error::Result<&mut Self> {
+        let dims = if dims.is_empty() {
+            None
+        } else {
+            Some(
+                dims.iter()
+                    .map(|&x| x.try_into())
+                    .collect::<Result<_, _>>()?,
+            )
+        };
+        self.comp.push(BuilderField {
+            typ: var.clone(),
+            name: super::utils::short_name_to_bytes(name)?,
+            dims,
+            offset,
+        });
+        Ok(self)
+    }
+
     /// Finalize the compound type
     pub fn build(self) -> error::Result<CompoundType> {
         let mut id = 0;
@@ -586,14 +869,19 @@ impl CompoundBuilder {
             nc_def_compound(self.ncid, self.size, self.name.as_ptr().cast(), &mut id)
         }))?;
 
-        let mut offset = 0;
-        for (typ, name, dims) in &self.comp {
+        for BuilderField {
+            typ,
+            name,
+            dims,
+            offset,
+        } in &self.comp
+        {
+            let offset = *offset;
             match dims {
                 None => {
                     error::checked(super::with_lock(|| unsafe {
                         nc_insert_compound(self.ncid, id, name.as_ptr().cast(), offset, typ.id())
                     }))?;
-                    offset += typ.size();
                 }
                 Some(dims) => {
                     let dimlen = dims.len().try_into().unwrap();
@@ -608,11 +896,6 @@ impl CompoundBuilder {
                             dims.as_ptr(),
                         )
                     }))?;
-                    offset += typ.size()
-                        * dims
-                            .iter()
-                            .map(|x: &i32| -> usize { (*x).try_into().unwrap() })
-                            .product::<usize>();
                 }
             }
         }
@@ -5,10 +5,11 @@ use super::attribute::AttrValue;
 use super::attribute::Attribute;
 use super::dimension::Dimension;
 use super::error;
-use super::types::VariableType;
+use super::types::{NcCompound, VariableType};
 #[cfg(feature = "ndarray")]
 use ndarray::ArrayD;
 use netcdf_sys::*;
+use std::borrow::Cow;
 use std::convert::TryInto;
 use std::ffi::CStr;
 use std::marker::PhantomData;
@@ -53,6 +54,150 @@ pub enum Endianness {
     Big,
 }
 
+/// A `HDF5` filter with parameters, as applied to a variable
+///
+/// The filter must be registered with the `HDF5` library,
+/// e.g. by setting `HDF5_PLUGIN_PATH` to the location of
+/// the plugins.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    /// Identifier of the filter, see the
+    /// [registered filters](https://portal.hdfgroup.org/display/support/Registered+Filter+Plugins)
+    pub id: u32,
+    /// Parameters passed to the filter
+    pub params: Vec<u32>,
+}
+
+/// Compressor used internally by the `blosc` filter
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BloscCompressor {
+    /// `BloscLZ`
+    BloscLZ,
+    /// `LZ4`
+    LZ4,
+    /// `LZ4HC`
+    LZ4HC,
+    /// `Snappy`
+    Snappy,
+    /// `Zlib`
+    Zlib,
+    /// `Zstd`
+    Zstd,
+}
+
+impl Filter {
+    /// Filter with the given identifier and parameters
+    pub fn new(id: u32, params: &[u32]) -> Self {
+        Self {
+            id,
+            params: params.to_vec(),
+        }
+    }
+
+    /// `zstandard` compression, `level` is usually in the range 1..=22
+    pub fn zstd(level: i32) -> Self {
+        #[allow(clippy::cast_sign_loss)]
+        Self::new(H5Z_FILTER_ZSTD, &[level as u32])
+    }
+
+    /// `bzip2` compression, `level` in the range 1..=9
+    pub fn bzip2(level: u32) -> Self {
+        Self::new(H5Z_FILTER_BZIP2, &[level])
+    }
+
+    /// `szip` compression
+    ///
+    /// `options_mask` is typically either `H5_SZIP_NN_OPTION_MASK`
+    /// or `H5_SZIP_EC_OPTION_MASK`, and `pixels_per_block`
+    /// must be an even number less than or equal to 32
+    pub fn szip(options_mask: u32, pixels_per_block: u32) -> Self {
+        Self::new(H5Z_FILTER_SZIP, &[options_mask, pixels_per_block])
+    }
+
+    /// `blosc` compression
+    ///
+    /// `level` in the range 0..=9, and `blocksize` of zero
+    /// lets the filter choose a blocksize
+    pub fn blosc(compressor: BloscCompressor, level: u32, blocksize: u32, shuffle: bool) -> Self {
+        // The first four parameters are reserved for the filter
+        // and computed when the variable is defined
+        Self::new(
+            H5Z_FILTER_BLOSC,
+            &[0, 0, 0, blocksize, level, shuffle.into(), compressor as u32],
+        )
+    }
+}
+
+/// Lossy quantization algorithms for floating point variables
+///
+/// Quantization sets the excess bits of the mantissa to
+/// a constant, making the data more compressible
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Quantization {
+    /// Retains a number of significant decimal digits,
+    /// alternately shaving and setting the excess bits
+    BitGroom,
+    /// Retains a number of significant decimal digits,
+    /// with the number of kept bits computed per value.
+    /// Requires `netCDF` 4.9.0
+    GranularBR,
+    /// Retains a number of significant bits of the mantissa.
+    /// Requires `netCDF` 4.9.0
+    BitRound,
+}
+
+/// Storage layout of a variable
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Storage {
+    /// Stored in chunks of the given size
+    Chunked(Vec<usize>),
+    /// Stored as a single contiguous block
+    Contiguous,
+    /// Stored in the metadata of the file,
+    /// only for small variables
+    Compact,
+}
+
+/// Compression and checksum settings of a variable
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CompressionSettings {
+    /// The shuffle filter is enabled
+    pub shuffle: bool,
+    /// Level of `deflate` compression, if enabled
+    pub deflate_level: Option<i32>,
+    /// The `fletcher32` checksum is enabled
+    pub fletcher32: bool,
+}
+
+/// Settings of the chunk cache of a variable
+///
+/// The cache holds decompressed chunks, and should be large
+/// enough to hold all chunks touched by a typical access pattern
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ChunkCache {
+    /// Size of the cache in bytes
+    pub size: usize,
+    /// Number of slots in the cache, should be a prime
+    /// number larger than the number of chunks in the cache
+    pub nelems: usize,
+    /// Preemption policy in the range 0.0..=1.0, where
+    /// 1.0 evicts fully read or written chunks first
+    pub preemption: f32,
+}
+
+/// Options for `deflate` compression of a variable
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DeflateOptions {
+    /// Compression level in the range 0..=9
+    pub level: i32,
+    /// Apply the shuffle filter before compressing,
+    /// which often improves compression of numeric data
+    pub shuffle: bool,
+}
+
+/// Start, count, and number of elements of a hyperslab
+type Slab<'a> = (Cow<'a, [usize]>, Cow<'a, [usize]>, usize);
+
 #[allow(clippy::len_without_is_empty)]
 impl<'g> Variable<'g> {
     pub(crate) fn find_from_name(ncid: nc_type, name: &str) -> error::Result<Option<Variable<'g>>> {
@@ -65,6 +210,11 @@ impl<'g> Variable<'g> {
         }
         error::checked(e)?;
 
+        Self::from_id(ncid, varid).map(Some)
+    }
+
+    /// The variable `varid` in the group `ncid`
+    pub(crate) fn from_id(ncid: nc_type, varid: nc_type) -> error::Result<Variable<'g>> {
         let mut xtype = 0;
         let mut ndims = 0;
         unsafe {
@@ -89,13 +239,13 @@ impl<'g> Variable<'g> {
         let dimensions = super::dimension::dimensions_from_variable(ncid, varid)?
             .collect::<error::Result<Vec<_>>>()?;
 
-        Ok(Some(Variable {
+        Ok(Variable {
             dimensions,
             ncid,
             varid,
             vartype: xtype,
             _group: PhantomData,
-        }))
+        })
     }
 
     /// Get name of variable
@@ -156,6 +306,242 @@ impl<'g> Variable<'g> {
             _ => Err(NC_EVARMETA.into()),
         }
     }
+
+    /// Get the storage layout of the variable.
+    /// Variables in classic files are always contiguous
+    ///
+    /// # Errors
+    ///
+    /// Unknown storage layout or `netCDF` error
+    pub fn storage(&self) -> error::Result<Storage> {
+        let mut storage = 0;
+        let mut chunksizes = vec![0; self.dimensions.len()];
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_inq_var_chunking(self.ncid, self.varid, &mut storage, chunksizes.as_mut_ptr())
+            }))?;
+        }
+        match storage {
+            NC_CHUNKED => Ok(Storage::Chunked(chunksizes)),
+            NC_CONTIGUOUS => Ok(Storage::Contiguous),
+            NC_COMPACT => Ok(Storage::Compact),
+            _ => Err(NC_EVARMETA.into()),
+        }
+    }
+
+    /// Get the chunk cache settings of the variable
+    ///
+    /// # Errors
+    ///
+    /// Not a `netCDF-4` file
+    pub fn chunk_cache(&self) -> error::Result<ChunkCache> {
+        let mut cache = ChunkCache {
+            size: 0,
+            nelems: 0,
+            preemption: 0.0,
+        };
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_get_var_chunk_cache(
+                    self.ncid,
+                    self.varid,
+                    &mut cache.size,
+                    &mut cache.nelems,
+                    &mut cache.preemption,
+                )
+            }))?;
+        }
+        Ok(cache)
+    }
+
+    /// Set the chunk cache of the variable. This only affects
+    /// the variable as long as the file is open, and can also
+    /// be used for files opened in read mode
+    ///
+    /// # Errors
+    ///
+    /// Not a `netCDF-4` file or invalid `preemption`
+    pub fn set_chunk_cache(&self, cache: ChunkCache) -> error::Result<()> {
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_set_var_chunk_cache(
+                    self.ncid,
+                    self.varid,
+                    cache.size,
+                    cache.nelems,
+                    cache.preemption,
+                )
+            }))
+        }
+    }
+
+    /// Get the compression and checksum settings of the variable.
+    /// Variables in classic files are never compressed
+    ///
+    /// # Errors
+    ///
+    /// `netCDF` error
+    pub fn compression_settings(&self) -> error::Result<CompressionSettings> {
+        let mut shuffle = 0;
+        let mut deflate = 0;
+        let mut deflate_level = 0;
+        let mut fletcher32 = 0;
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_inq_var_deflate(
+                    self.ncid,
+                    self.varid,
+                    &mut shuffle,
+                    &mut deflate,
+                    &mut deflate_level,
+                )
+            }))?;
+            error::checked(super::with_lock(|| {
+                nc_inq_var_fletcher32(self.ncid, self.varid, &mut fletcher32)
+            }))?;
+        }
+        Ok(CompressionSettings {
+            shuffle: shuffle != NC_NOSHUFFLE,
+            deflate_level: if deflate == 0 {
+                None
+            } else {
+                Some(deflate_level)
+            },
+            fletcher32: fletcher32 != NC_NOCHECKSUM,
+        })
+    }
+
+    /// Get the filters applied to the variable, in the order
+    /// they are applied when writing
+    ///
+    /// Versions of `netCDF` prior to `4.8.0` only reports
+    /// the first filter of the variable
+    ///
+    /// # Errors
+    ///
+    /// Not a `netCDF-4` file
+    #[cfg(feature = "4.8.0")]
+    pub fn filters(&self) -> error::Result<Vec<Filter>> {
+        let mut nfilters = 0;
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_inq_var_filter_ids(self.ncid, self.varid, &mut nfilters, std::ptr::null_mut())
+            }))?;
+        }
+        let mut ids = vec![0; nfilters];
+        if nfilters > 0 {
+            unsafe {
+                error::checked(super::with_lock(|| {
+                    nc_inq_var_filter_ids(self.ncid, self.varid, &mut nfilters, ids.as_mut_ptr())
+                }))?;
+            }
+        }
+
+        ids.into_iter()
+            .map(|id| {
+                let mut nparams = 0;
+                unsafe {
+                    error::checked(super::with_lock(|| {
+                        nc_inq_var_filter_info(
+                            self.ncid,
+                            self.varid,
+                            id,
+                            &mut nparams,
+                            std::ptr::null_mut(),
+                        )
+                    }))?;
+                }
+                let mut params = vec![0; nparams];
+                if nparams > 0 {
+                    unsafe {
+                        error::checked(super::with_lock(|| {
+                            nc_inq_var_filter_info(
+                                self.ncid,
+                                self.varid,
+                                id,
+                                &mut nparams,
+                                params.as_mut_ptr(),
+                            )
+                        }))?;
+                    }
+                }
+                Ok(Filter { id, params })
+            })
+            .collect()
+    }
+
+    /// Get the filters applied to the variable, in the order
+    /// they are applied when writing
+    ///
+    /// Versions of `netCDF` prior to `4.8.0` only reports
+    /// the first filter of the variable
+    ///
+    /// # Errors
+    ///
+    /// Not a `netCDF-4` file
+    #[cfg(not(feature = "4.8.0"))]
+    pub fn filters(&self) -> error::Result<Vec<Filter>> {
+        let mut id = 0;
+        let mut nparams = 0;
+        let e = unsafe {
+            super::with_lock(|| {
+                nc_inq_var_filter(
+                    self.ncid,
+                    self.varid,
+                    &mut id,
+                    &mut nparams,
+                    std::ptr::null_mut(),
+                )
+            })
+        };
+        if e == NC_ENOFILTER {
+            return Ok(Vec::new());
+        }
+        error::checked(e)?;
+        if id == 0 {
+            return Ok(Vec::new());
+        }
+        let mut params = vec![0; nparams];
+        if nparams > 0 {
+            unsafe {
+                error::checked(super::with_lock(|| {
+                    nc_inq_var_filter(
+                        self.ncid,
+                        self.varid,
+                        &mut id,
+                        &mut nparams,
+                        params.as_mut_ptr(),
+                    )
+                }))?;
+            }
+        }
+        Ok(vec![Filter { id, params }])
+    }
+
+    /// Get the quantization applied to the variable,
+    /// together with the number of significant digits (or bits
+    /// for [`Quantization::BitRound`])
+    ///
+    /// # Errors
+    ///
+    /// Not a `netCDF-4` file
+    #[cfg(feature = "4.8.1")]
+    pub fn quantization(&self) -> error::Result<Option<(Quantization, i32)>> {
+        let mut mode = 0;
+        let mut nsd = 0;
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_inq_var_quantize(self.ncid, self.varid, &mut mode, &mut nsd)
+            }))?;
+        }
+        match mode {
+            NC_NOQUANTIZE => Ok(None),
+            NC_QUANTIZE_BITGROOM => Ok(Some((Quantization::BitGroom, nsd))),
+            NC_QUANTIZE_GRANULARBR => Ok(Some((Quantization::GranularBR, nsd))),
+            NC_QUANTIZE_BITROUND => Ok(Some((Quantization::BitRound, nsd))),
+            _ => Err(NC_EVARMETA.into()),
+        }
+    }
 }
 impl<'g> VariableMut<'g> {
     /// Sets compression on the variable. Must be set before filling in data.
@@ -177,6 +563,73 @@ impl<'g> VariableMut<'g> {
         Ok(())
     }
 
+    /// Sets `deflate` compression and shuffling on the variable.
+    /// Must be set before inserting data
+    ///
+    /// # Errors
+    ///
+    /// Not a `netcdf-4` file or `level` not valid
+    pub fn deflate(&mut self, options: DeflateOptions) -> error::Result<()> {
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_def_var_deflate(
+                    self.ncid,
+                    self.varid,
+                    options.shuffle.into(),
+                    true.into(),
+                    options.level,
+                )
+            }))?;
+        }
+
+        Ok(())
+    }
+
+    /// Enables or disables the shuffle filter on the variable,
+    /// keeping the current `deflate` setting.
+    /// Must be set before inserting data
+    ///
+    /// # Errors
+    ///
+    /// Not a `netcdf-4` file
+    pub fn shuffle(&mut self, shuffle: bool) -> error::Result<()> {
+        let deflate_level = self.compression_settings()?.deflate_level;
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_def_var_deflate(
+                    self.ncid,
+                    self.varid,
+                    shuffle.into(),
+                    deflate_level.is_some().into(),
+                    deflate_level.unwrap_or(0),
+                )
+            }))?;
+        }
+
+        Ok(())
+    }
+
+    /// Enables or disables the `fletcher32` checksum on the variable.
+    /// Must be set before inserting data
+    ///
+    /// # Errors
+    ///
+    /// Not a `netcdf-4` file
+    pub fn fletcher32(&mut self, fletcher32: bool) -> error::Result<()> {
+        let fletcher32 = if fletcher32 {
+            NC_FLETCHER32
+        } else {
+            NC_NOCHECKSUM
+        };
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_def_var_fletcher32(self.ncid, self.varid, fletcher32)
+            }))?;
+        }
+
+        Ok(())
+    }
+
     /// Set chunking for variable. Must be set before inserting data
     ///
     /// Use this when reading or writing smaller units of the hypercube than
@@ -209,6 +662,100 @@ impl<'g> VariableMut<'g> {
 
         Ok(())
     }
+
+    /// Set the storage layout of the variable. Must be set before inserting data
+    ///
+    /// [`Storage::Compact`] is only possible for small variables
+    /// (at most 64 KiB) without unlimited dimensions, and
+    /// [`Storage::Contiguous`] can not be combined with filters
+    ///
+    /// # Errors
+    ///
+    /// Not a `netCDF-4` file, invalid chunksize, or
+    /// the data is too large for compact storage
+    pub fn set_storage(&mut self, storage: Storage) -> error::Result<()> {
+        match storage {
+            Storage::Chunked(chunksize) => self.chunking(&chunksize),
+            Storage::Contiguous => unsafe {
+                error::checked(super::with_lock(|| {
+                    nc_def_var_chunking(self.ncid, self.varid, NC_CONTIGUOUS, std::ptr::null())
+                }))
+            },
+            Storage::Compact => {
+                // Compact data is stored in the object header, which
+                // has a limit of 64 KiB. The size of variable length
+                // types can not be known in advance
+                const COMPACT_MAX_SIZE: usize = 64 * 1024;
+                match self.vartype() {
+                    VariableType::String | VariableType::Vlen(_) => {}
+                    vartype => {
+                        let size = self.len().saturating_mul(vartype.size());
+                        if size > COMPACT_MAX_SIZE {
+                            return Err(NC_EVARSIZE.into());
+                        }
+                    }
+                }
+                unsafe {
+                    error::checked(super::with_lock(|| {
+                        nc_def_var_chunking(self.ncid, self.varid, NC_COMPACT, std::ptr::null())
+                    }))
+                }
+            }
+        }
+    }
+
+    /// Adds a filter to the variable. Must be set before inserting data
+    ///
+    /// Filters are applied in the order they are added. Using
+    /// a filter implies chunking of the variable
+    ///
+    /// # Errors
+    ///
+    /// Not a `netCDF-4` file, or the filter is not available
+    /// (`NC_ENOFILTER`) or rejected the parameters
+    pub fn add_filter(&mut self, filter: &Filter) -> error::Result<()> {
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_def_var_filter(
+                    self.ncid,
+                    self.varid,
+                    filter.id,
+                    filter.params.len(),
+                    filter.params.as_ptr(),
+                )
+            }))?;
+        }
+
+        Ok(())
+    }
+
+    /// Sets lossy quantization on the variable, keeping `nsd`
+    /// significant digits (or bits for [`Quantization::BitRound`]).
+    /// Must be set before inserting data
+    ///
+    /// # Errors
+    ///
+    /// Not a `netCDF-4` file, `nsd` out of range, or
+    /// [`error::Error::TypeMismatch`] if the variable is not
+    /// of type `f32` or `f64`
+    #[cfg(feature = "4.8.1")]
+    pub fn quantize(&mut self, mode: Quantization, nsd: i32) -> error::Result<()> {
+        if self.vartype != NC_FLOAT && self.vartype != NC_DOUBLE {
+            return Err(error::Error::TypeMismatch);
+        }
+        let mode = match mode {
+            Quantization::BitGroom => NC_QUANTIZE_BITGROOM,
+            Quantization::GranularBR => NC_QUANTIZE_GRANULARBR,
+            Quantization::BitRound => NC_QUANTIZE_BITROUND,
+        };
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_def_var_quantize(self.ncid, self.varid, mode, nsd)
+            }))?;
+        }
+
+        Ok(())
+    }
 }
 
 impl<'g> Variable<'g> {
@@ -343,6 +890,55 @@ impl<'g> Variable<'g> {
         }
         Ok(sizelen)
     }
+
+    /// Resolves the start and count of a hyperslab, defaulting to the
+    /// whole variable, together with the number of elements. This is
+    /// `len` if given, and otherwise follows from the slab
+    fn resolve_slab<'a>(
+        &self,
+        indices: Option<&'a [usize]>,
+        slice_len: Option<&'a [usize]>,
+        len: Option<usize>,
+        putting: bool,
+    ) -> error::Result<Slab<'a>> {
+        let indices = if let Some(x) = indices {
+            self.check_indices(x, putting)?;
+            Cow::Borrowed(x)
+        } else {
+            Cow::Owned(self.default_indices(putting)?)
+        };
+        if let Some(x) = slice_len {
+            let len = if let Some(len) = len {
+                len
+            } else {
+                let len = x.iter().fold(1_usize, |acc, x| acc.saturating_mul(*x));
+                if len == usize::MAX {
+                    return Err(error::Error::Overflow);
+                }
+                len
+            };
+            self.check_sizelen(len, &indices, x, putting)?;
+            Ok((indices, Cow::Borrowed(x), len))
+        } else {
+            let len = len.unwrap_or_else(|| self.dimensions.iter().map(Dimension::len).product());
+            let slice_len = self.default_sizelen(len, &indices, putting)?;
+            Ok((indices, Cow::Owned(slice_len), len))
+        }
+    }
+}
+
+mod sealed {
+    /// Restricts implementations of traits to the
+    /// [`Numeric`](super::Numeric) types of this crate
+    pub trait Sealed {}
+}
+
+/// Conversion of numeric values to doubles, used when comparing
+/// with attributes. This trait is sealed and implemented for all
+/// [`Numeric`] types
+pub trait AsF64: sealed::Sealed {
+    /// The value as a double
+    fn as_f64(&self) -> f64;
 }
 
 #[allow(clippy::doc_markdown)]
@@ -360,6 +956,9 @@ where
     /// Constant corresponding to a netcdf type
     const NCTYPE: nc_type;
 
+    /// Fill value used by `netCDF` when `_FillValue` is not set
+    const DEFAULT_FILL: Self;
+
     /// Returns a single indexed value of the variable as Self
     ///
     /// # Safety
@@ -452,6 +1051,7 @@ macro_rules! impl_numeric {
     (
         $sized_type: ty,
         $nc_type: ident,
+        $default_fill: expr,
         $nc_get_var: ident,
         $nc_get_vara_type: ident,
         $nc_get_var1_type: ident,
@@ -460,9 +1060,19 @@ macro_rules! impl_numeric {
         $nc_get_vars_type: ident,
         $nc_put_vars_type: ident,
     ) => {
+        impl sealed::Sealed for $sized_type {}
+
+        impl AsF64 for $sized_type {
+            #[allow(clippy::cast_precision_loss, clippy::cast_lossless)]
+            fn as_f64(&self) -> f64 {
+                *self as f64
+            }
+        }
+
         #[allow(clippy::use_self)] // False positives
         unsafe impl Numeric for $sized_type {
             const NCTYPE: nc_type = $nc_type;
+            const DEFAULT_FILL: Self = $default_fill;
 
             // fetch ONE value from variable using `$nc_get_var1`
             unsafe fn single_value_from_variable(
@@ -568,6 +1178,7 @@ macro_rules! impl_numeric {
 impl_numeric!(
     u8,
     NC_UBYTE,
+    NC_FILL_UBYTE as u8,
     nc_get_var_uchar,
     nc_get_vara_uchar,
     nc_get_var1_uchar,
@@ -580,6 +1191,7 @@ impl_numeric!(
 impl_numeric!(
     i8,
     NC_BYTE,
+    NC_FILL_BYTE,
     nc_get_var_schar,
     nc_get_vara_schar,
     nc_get_var1_schar,
@@ -592,6 +1204,7 @@ impl_numeric!(
 impl_numeric!(
     i16,
     NC_SHORT,
+    NC_FILL_SHORT,
     nc_get_var_short,
     nc_get_vara_short,
     nc_get_var1_short,
@@ -604,6 +1217,7 @@ impl_numeric!(
 impl_numeric!(
     u16,
     NC_USHORT,
+    NC_FILL_USHORT as u16,
     nc_get_var_ushort,
     nc_get_vara_ushort,
     nc_get_var1_ushort,
@@ -616,6 +1230,7 @@ impl_numeric!(
 impl_numeric!(
     i32,
     NC_INT,
+    NC_FILL_INT,
     nc_get_var_int,
     nc_get_vara_int,
     nc_get_var1_int,
@@ -628,6 +1243,7 @@ impl_numeric!(
 impl_numeric!(
     u32,
     NC_UINT,
+    NC_FILL_UINT,
     nc_get_var_uint,
     nc_get_vara_uint,
     nc_get_var1_uint,
@@ -640,6 +1256,7 @@ impl_numeric!(
 impl_numeric!(
     i64,
     NC_INT64,
+    NC_FILL_INT64,
     nc_get_var_longlong,
     nc_get_vara_longlong,
     nc_get_var1_longlong,
@@ -652,6 +1269,7 @@ impl_numeric!(
 impl_numeric!(
     u64,
     NC_UINT64,
+    NC_FILL_UINT64,
     nc_get_var_ulonglong,
     nc_get_vara_ulonglong,
     nc_get_var1_ulonglong,
@@ -664,6 +1282,7 @@ impl_numeric!(
 impl_numeric!(
     f32,
     NC_FLOAT,
+    NC_FILL_FLOAT,
     nc_get_var_float,
     nc_get_vara_float,
     nc_get_var1_float,
@@ -676,6 +1295,7 @@ impl_numeric!(
 impl_numeric!(
     f64,
     NC_DOUBLE,
+    NC_FILL_DOUBLE,
     nc_get_var_double,
     nc_get_vara_double,
     nc_get_var1_double,
@@ -685,9 +1305,134 @@ impl_numeric!(
     nc_put_vars_double,
 );
 
-/// Holds the contents of a netcdf string. Use deref to get a `CStr`
-struct NcString {
-    data: *mut std::os::raw::c_char,
+/// Vlen elements allocated by `netCDF`, which are freed on drop
+struct VlenBuffer(Vec<nc_vlen_t>);
+
+impl Drop for VlenBuffer {
+    fn drop(&mut self) {
+        let len = self.0.len();
+        let ptr = self.0.as_mut_ptr();
+        error::checked(super::with_lock(|| unsafe { nc_free_vlens(len, ptr) })).unwrap();
+    }
+}
+
+/// Values which are masked when reading
+#[derive(Debug, Clone)]
+struct Mask {
+    fill_value: Option<f64>,
+    missing_values: Vec<f64>,
+    valid_min: Option<f64>,
+    valid_max: Option<f64>,
+}
+
+impl Mask {
+    fn is_masked(&self, x: f64) -> bool {
+        let equal = |y: &f64| x == *y || (x.is_nan() && y.is_nan());
+        self.fill_value.iter().any(equal)
+            || self.missing_values.iter().any(equal)
+            || self.valid_min.iter().any(|&min| x < min)
+            || self.valid_max.iter().any(|&max| x > max)
+    }
+}
+
+/// Parameters of CF packing, where
+/// `unpacked = packed * scale_factor + add_offset`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Packing {
+    /// Multiplied with the packed values
+    pub scale_factor: f64,
+    /// Added to the packed values after scaling
+    pub add_offset: f64,
+}
+
+impl Packing {
+    /// Unpack a single value
+    pub fn unpack(&self, packed: f64) -> f64 {
+        packed * self.scale_factor + self.add_offset
+    }
+    /// Pack a single value, without rounding
+    pub fn pack(&self, unpacked: f64) -> f64 {
+        (unpacked - self.add_offset) / self.scale_factor
+    }
+}
+
+/// Floating point types which packed values can be unpacked to.
+/// This trait is sealed and implemented for `f32` and `f64`
+pub trait Unpacked: Numeric + Copy + Into<AttrValue> + sealed::Sealed {
+    /// Convert from a double
+    fn from_f64(x: f64) -> Self;
+    /// Convert to a double
+    fn to_f64(self) -> f64;
+}
+
+impl Unpacked for f32 {
+    #[allow(clippy::cast_possible_truncation)]
+    fn from_f64(x: f64) -> Self {
+        x as Self
+    }
+    fn to_f64(self) -> f64 {
+        self.into()
+    }
+}
+
+impl Unpacked for f64 {
+    fn from_f64(x: f64) -> Self {
+        x
+    }
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+/// Encoding of the characters of a char variable
+#[derive(Debug, Clone, Copy)]
+enum CharEncoding {
+    Utf8,
+    Ascii,
+    Latin1,
+}
+
+impl CharEncoding {
+    /// Decodes a fixed width string, cut at the first NUL
+    /// and without trailing spaces
+    fn decode(self, bytes: &[u8]) -> String {
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        let bytes = &bytes[..end];
+        let s = match self {
+            Self::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+            Self::Ascii | Self::Latin1 => bytes.iter().map(|&b| char::from(b)).collect(),
+        };
+        s.trim_end_matches(' ').to_owned()
+    }
+
+    fn encode(self, s: &str) -> error::Result<Vec<u8>> {
+        match self {
+            Self::Utf8 => Ok(s.as_bytes().to_vec()),
+            Self::Ascii if s.is_ascii() => Ok(s.as_bytes().to_vec()),
+            Self::Latin1 => s
+                .chars()
+                .map(|c| -> Result<u8, char> { u32::from(c).try_into().map_err(|_| c) })
+                .collect::<Result<_, _>>()
+                .map_err(|c| format!("can not encode {:?} as iso-8859-1", c).into()),
+            Self::Ascii => Err(format!("can not encode {:?} as ascii", s).into()),
+        }
+    }
+}
+
+/// Strings allocated by `netCDF`, which are freed on drop
+struct NcStrings(Vec<*mut std::os::raw::c_char>);
+
+impl Drop for NcStrings {
+    fn drop(&mut self) {
+        let len = self.0.len();
+        let ptr = self.0.as_mut_ptr();
+        error::checked(super::with_lock(|| unsafe { nc_free_string(len, ptr) })).unwrap();
+    }
+}
+
+/// Holds the contents of a netcdf string. Use deref to get a `CStr`
+struct NcString {
+    data: *mut std::os::raw::c_char,
 }
 impl NcString {
     /// Create an `NcString`
@@ -717,6 +1462,37 @@ impl<'g> VariableMut<'g> {
     {
         Attribute::put(self.ncid, self.varid, name, val.into())
     }
+
+    /// Rename the variable
+    ///
+    /// # Errors
+    ///
+    /// [`error::Error::AlreadyExists`] if the name is in use
+    pub fn rename(&mut self, name: &str) -> error::Result<()> {
+        let cname = super::utils::short_name_to_bytes(name)?;
+        super::utils::checked_with_redef(self.ncid, || unsafe {
+            nc_rename_var(self.ncid, self.varid, cname.as_ptr().cast())
+        })
+    }
+
+    /// Rename an attribute of the variable
+    ///
+    /// # Errors
+    ///
+    /// [`error::Error::NotFound`] if the attribute does not exist, or
+    /// [`error::Error::AlreadyExists`] if the new name is in use
+    pub fn rename_attribute(&mut self, oldname: &str, newname: &str) -> error::Result<()> {
+        Attribute::rename(self.ncid, Some(self.varid), oldname, newname)
+    }
+
+    /// Remove an attribute from the variable
+    ///
+    /// # Errors
+    ///
+    /// [`error::Error::NotFound`] if the attribute does not exist
+    pub fn remove_attribute(&mut self, name: &str) -> error::Result<()> {
+        Attribute::remove(self.ncid, Some(self.varid), name)
+    }
 }
 
 impl<'g> Variable<'g> {
@@ -757,6 +1533,63 @@ impl<'g> Variable<'g> {
         Ok(string.to_string_lossy().into_owned())
     }
 
+    /// Get multiple strings from a string variable, read in a single call
+    pub fn string_values(
+        &self,
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<Vec<String>> {
+        self.string_values_with_shape(indices, slice_len)
+            .map(|(values, _)| values)
+    }
+
+    #[cfg(feature = "ndarray")]
+    /// Get multiple strings from a string variable as an array,
+    /// read in a single call
+    pub fn string_values_array(
+        &self,
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<ArrayD<String>> {
+        let (values, shape) = self.string_values_with_shape(indices, slice_len)?;
+        Ok(ArrayD::from_shape_vec(shape, values).unwrap())
+    }
+
+    fn string_values_with_shape(
+        &self,
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<(Vec<String>, Vec<usize>)> {
+        let (indices, slice_len, full_length) =
+            self.resolve_slab(indices, slice_len, None, false)?;
+
+        let mut strings = NcStrings(vec![std::ptr::null_mut(); full_length]);
+        error::checked(super::with_lock(|| unsafe {
+            nc_get_vara_string(
+                self.ncid,
+                self.varid,
+                indices.as_ptr(),
+                slice_len.as_ptr(),
+                strings.0.as_mut_ptr(),
+            )
+        }))?;
+
+        let values = strings
+            .0
+            .iter()
+            .map(|&ptr| {
+                if ptr.is_null() {
+                    String::new()
+                } else {
+                    unsafe { CStr::from_ptr(ptr) }
+                        .to_string_lossy()
+                        .into_owned()
+                }
+            })
+            .collect();
+        Ok((values, slice_len.to_vec()))
+    }
+
     #[cfg(feature = "ndarray")]
     /// Fetches variable
     pub fn values<T: Numeric>(
@@ -818,6 +1651,203 @@ impl<'g> Variable<'g> {
 
         Ok(Some(unsafe { location.assume_init() }))
     }
+
+    /// First value of a numeric attribute
+    fn attribute_f64(&self, name: &str) -> error::Result<Option<f64>> {
+        match self.attribute(name) {
+            None => Ok(None),
+            Some(attr) => match attr.value()?.to_f64s() {
+                Some(values) if !values.is_empty() => Ok(Some(values[0])),
+                _ => Err(error::Error::TypeMismatch),
+            },
+        }
+    }
+
+    /// Values to be masked, as given by the attributes `_FillValue`,
+    /// `missing_value`, `valid_min`, `valid_max` and `valid_range`
+    fn mask(&self) -> error::Result<Mask> {
+        let basic = match self.vartype() {
+            VariableType::Basic(basic) => basic,
+            _ => return Err(error::Error::TypeMismatch),
+        };
+        let fill_value = match self.attribute_f64("_FillValue")? {
+            Some(fill_value) => Some(fill_value),
+            // The default fill value is not used for byte types
+            None if basic.size() == 1 => None,
+            None => Some(basic.default_fill()),
+        };
+        let missing_values = match self.attribute("missing_value") {
+            None => Vec::new(),
+            Some(attr) => attr.value()?.to_f64s().ok_or(error::Error::TypeMismatch)?,
+        };
+        let (valid_min, valid_max) = match self.attribute("valid_range") {
+            Some(attr) => match attr.value()?.to_f64s().as_deref() {
+                Some(&[min, max]) => (Some(min), Some(max)),
+                _ => return Err(error::Error::TypeMismatch),
+            },
+            None => (
+                self.attribute_f64("valid_min")?,
+                self.attribute_f64("valid_max")?,
+            ),
+        };
+        Ok(Mask {
+            fill_value,
+            missing_values,
+            valid_min,
+            valid_max,
+        })
+    }
+
+    /// Reads values, replacing masked values by `None`
+    fn masked_values<T: Numeric + AsF64>(
+        &self,
+        indices: &[usize],
+        slice_len: &[usize],
+        len: usize,
+    ) -> error::Result<Vec<Option<T>>> {
+        let mask = self.mask()?;
+        let mut values = Vec::with_capacity(len);
+        unsafe {
+            T::variable_to_ptr(self, indices, slice_len, values.as_mut_ptr())?;
+            values.set_len(len);
+        }
+        Ok(values
+            .into_iter()
+            .map(|x| {
+                if mask.is_masked(x.as_f64()) {
+                    None
+                } else {
+                    Some(x)
+                }
+            })
+            .collect())
+    }
+
+    #[cfg(feature = "ndarray")]
+    /// Fetches variable, where values equal to `_FillValue` (or the default
+    /// fill value if not set, except for byte types) or `missing_value`,
+    /// or outside `valid_range` (or `valid_min` and `valid_max`) are `None`
+    pub fn values_masked<T: Numeric + AsF64>(
+        &self,
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<ArrayD<Option<T>>> {
+        let (indices, slice_len, full_length) =
+            self.resolve_slab(indices, slice_len, None, false)?;
+
+        let values = self.masked_values(&indices, &slice_len, full_length)?;
+        Ok(ArrayD::from_shape_vec(&*slice_len, values).unwrap())
+    }
+
+    /// Fetches variable into slice, where masked values are `None`,
+    /// see [`Variable::values_masked`] for the rules.
+    /// buffer must be able to hold all the requested elements
+    pub fn values_masked_to<T: Numeric + AsF64>(
+        &self,
+        buffer: &mut [Option<T>],
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<()> {
+        let (indices, slice_len, _) =
+            self.resolve_slab(indices, slice_len, Some(buffer.len()), false)?;
+
+        let values = self.masked_values(&indices, &slice_len, buffer.len())?;
+        for (b, v) in buffer.iter_mut().zip(values) {
+            *b = v;
+        }
+        Ok(())
+    }
+
+    /// Get the packing parameters from the `scale_factor` and
+    /// `add_offset` attributes, with defaults 1 and 0
+    pub fn packing(&self) -> error::Result<Packing> {
+        Ok(Packing {
+            scale_factor: self.attribute_f64("scale_factor")?.unwrap_or(1.0),
+            add_offset: self.attribute_f64("add_offset")?.unwrap_or(0.0),
+        })
+    }
+
+    #[cfg(feature = "ndarray")]
+    /// Get values of a packed variable, unpacked as given by [`Variable::packing`].
+    /// Masked values, see [`Variable::values_masked`], are returned as NaN
+    pub fn values_decoded<T: Unpacked>(
+        &self,
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<ArrayD<T>> {
+        let (indices, slice_len, full_length) =
+            self.resolve_slab(indices, slice_len, None, false)?;
+
+        let values = self.decoded_values(&indices, &slice_len, full_length)?;
+        Ok(ArrayD::from_shape_vec(&*slice_len, values).unwrap())
+    }
+
+    /// Fetches variable into slice, unpacked as in [`Variable::values_decoded`].
+    /// buffer must be able to hold all the requested elements
+    pub fn values_decoded_to<T: Unpacked>(
+        &self,
+        buffer: &mut [T],
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<()> {
+        let (indices, slice_len, _) =
+            self.resolve_slab(indices, slice_len, Some(buffer.len()), false)?;
+
+        let values = self.decoded_values(&indices, &slice_len, buffer.len())?;
+        buffer.copy_from_slice(&values);
+        Ok(())
+    }
+
+    /// Reads and unpacks values, where masked values become NaN
+    fn decoded_values<T: Unpacked>(
+        &self,
+        indices: &[usize],
+        slice_len: &[usize],
+        len: usize,
+    ) -> error::Result<Vec<T>> {
+        let packing = self.packing()?;
+        let mask = self.mask()?;
+
+        let mut packed = vec![0.0_f64; len];
+        self.values_to(&mut packed, Some(indices), Some(slice_len))?;
+        Ok(packed
+            .into_iter()
+            .map(|x| {
+                if mask.is_masked(x) {
+                    T::from_f64(f64::NAN)
+                } else {
+                    T::from_f64(packing.unpack(x))
+                }
+            })
+            .collect())
+    }
+
+    /// Get the fill value which is used for unwritten values, which is
+    /// `_FillValue` if set and [`Numeric::DEFAULT_FILL`] otherwise.
+    /// Gives `None` if the variable is not filled
+    ///
+    /// # Errors
+    ///
+    /// [`error::Error::TypeMismatch`] if `T` is not the type of the variable
+    pub fn effective_fill_value<T: Numeric>(&self) -> error::Result<Option<T>> {
+        if T::NCTYPE != self.vartype {
+            return Err(error::Error::TypeMismatch);
+        }
+        let mut nofill: nc_type = 0;
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_inq_var_fill(self.ncid, self.varid, &mut nofill, std::ptr::null_mut())
+            }))?;
+        }
+        if nofill == 1 {
+            return Ok(None);
+        }
+        if self.attribute("_FillValue").is_none() {
+            return Ok(Some(T::DEFAULT_FILL));
+        }
+        self.fill_value()
+    }
+
     /// Fetches variable into slice
     /// buffer must be able to hold all the requested elements
     pub fn values_to<T: Numeric>(
@@ -957,42 +1987,364 @@ impl<'g> Variable<'g> {
 
     /// Get a vlen element
     pub fn vlen<T: Numeric>(&self, index: &[usize]) -> error::Result<Vec<T>> {
-        if let super::types::VariableType::Vlen(v) = self.vartype() {
-            if v.typ().id() != T::NCTYPE {
-                return Err(error::Error::TypeMismatch);
-            }
-        } else {
+        let count = index.iter().map(|_| 1).collect::<Vec<usize>>();
+        let mut values = self.vlen_values::<T>(Some(index), Some(&count))?;
+        Ok(values.pop().unwrap())
+    }
+
+    /// Checks that the variable is a vlen of `T`
+    fn check_vlen<T: Numeric>(&self) -> error::Result<()> {
+        match self.vartype() {
+            VariableType::Vlen(v) if v.typ().id() == T::NCTYPE => Ok(()),
+            _ => Err(error::Error::TypeMismatch),
+        }
+    }
+
+    /// Get multiple vlen elements, read in a single call
+    ///
+    /// # Errors
+    ///
+    /// [`error::Error::TypeMismatch`] if the variable is not a vlen of `T`
+    pub fn vlen_values<T: Numeric>(
+        &self,
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<Vec<Vec<T>>> {
+        self.vlen_values_with_shape(indices, slice_len)
+            .map(|(values, _)| values)
+    }
+
+    #[cfg(feature = "ndarray")]
+    /// Get multiple vlen elements as an array, read in a single call
+    ///
+    /// # Errors
+    ///
+    /// [`error::Error::TypeMismatch`] if the variable is not a vlen of `T`
+    pub fn vlen_values_array<T: Numeric>(
+        &self,
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<ArrayD<Vec<T>>> {
+        let (values, shape) = self.vlen_values_with_shape(indices, slice_len)?;
+        Ok(ArrayD::from_shape_vec(shape, values).unwrap())
+    }
+
+    fn vlen_values_with_shape<T: Numeric>(
+        &self,
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<(Vec<Vec<T>>, Vec<usize>)> {
+        self.check_vlen::<T>()?;
+        let (indices, slice_len, full_length) =
+            self.resolve_slab(indices, slice_len, None, false)?;
+
+        let mut buffer = VlenBuffer(
+            (0..full_length)
+                .map(|_| nc_vlen_t {
+                    len: 0,
+                    p: std::ptr::null_mut(),
+                })
+                .collect(),
+        );
+        error::checked(super::with_lock(|| unsafe {
+            nc_get_vara(
+                self.ncid,
+                self.varid,
+                indices.as_ptr(),
+                slice_len.as_ptr(),
+                buffer.0.as_mut_ptr().cast(),
+            )
+        }))?;
+
+        let values = buffer
+            .0
+            .iter()
+            .map(|vlen| {
+                if vlen.len == 0 {
+                    return Vec::new();
+                }
+                let mut v = Vec::<T>::with_capacity(vlen.len);
+                unsafe {
+                    std::ptr::copy_nonoverlapping(vlen.p as *const T, v.as_mut_ptr(), vlen.len);
+                    v.set_len(vlen.len);
+                }
+                v
+            })
+            .collect();
+        Ok((values, slice_len.to_vec()))
+    }
+
+    /// Get the size of the opaque type of this variable
+    fn opaque_size(&self) -> error::Result<usize> {
+        match self.vartype() {
+            VariableType::Opaque(o) => Ok(o.size()),
+            _ => Err(error::Error::TypeMismatch),
+        }
+    }
+
+    /// Get values of an opaque variable, one blob per element
+    ///
+    /// # Errors
+    ///
+    /// [`error::Error::TypeMismatch`] if the variable is not opaque
+    pub fn opaque_values(
+        &self,
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<Vec<Vec<u8>>> {
+        let size = self.opaque_size()?;
+        let (indices, slice_len, full_length) =
+            self.resolve_slab(indices, slice_len, None, false)?;
+
+        let mut buf = vec![0_u8; full_length * size];
+        error::checked(super::with_lock(|| unsafe {
+            nc_get_vara(
+                self.ncid,
+                self.varid,
+                indices.as_ptr(),
+                slice_len.as_ptr(),
+                buf.as_mut_ptr().cast(),
+            )
+        }))?;
+        Ok(buf.chunks(size).map(<[u8]>::to_vec).collect())
+    }
+
+    /// Encoding of a char variable, given by the `_Encoding` attribute
+    fn char_encoding(&self) -> error::Result<CharEncoding> {
+        if !matches!(
+            self.vartype(),
+            VariableType::Basic(super::types::BasicType::Char)
+        ) {
             return Err(error::Error::TypeMismatch);
+        }
+        let encoding = match self.attribute("_Encoding") {
+            None => return Ok(CharEncoding::Utf8),
+            Some(attr) => match attr.value()? {
+                AttrValue::Str(s) => s,
+                _ => return Err(error::Error::TypeMismatch),
+            },
         };
+        match encoding.to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" => Ok(CharEncoding::Utf8),
+            "ascii" | "us-ascii" => Ok(CharEncoding::Ascii),
+            "iso-8859-1" | "iso8859-1" | "latin1" | "latin-1" => Ok(CharEncoding::Latin1),
+            _ => Err(format!("unsupported _Encoding: {}", encoding).into()),
+        }
+    }
 
-        self.check_indices(index, false)?;
+    /// Start and count of a slab of a char variable, where `indices`
+    /// and `slice_len` do not include the last (string) dimension
+    fn char_slab(
+        &self,
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+        putting: Option<usize>,
+    ) -> error::Result<(Vec<usize>, Vec<usize>)> {
+        let (strlen, outer) = match self.dimensions.split_last() {
+            Some((last, outer)) => {
+                if last.is_unlimited() {
+                    return Err("the string dimension can not be unlimited".into());
+                }
+                (last.len(), outer.len())
+            }
+            None => (1, 0),
+        };
+        let with_strlen = |x: &[usize], last: usize| {
+            let mut x = x.to_vec();
+            if !self.dimensions.is_empty() {
+                x.push(last);
+            }
+            x
+        };
 
-        let mut vlen = nc_vlen_t {
-            len: 0,
-            p: std::ptr::null_mut(),
+        let indices = if let Some(x) = indices {
+            if x.len() != outer {
+                return Err(error::Error::IndexLen);
+            }
+            let x = with_strlen(x, 0);
+            self.check_indices(&x, putting.is_some())?;
+            x
+        } else {
+            self.default_indices(putting.is_some())?
         };
+        let slice_len = if let Some(x) = slice_len {
+            if x.len() != outer {
+                return Err(error::Error::SliceLen);
+            }
+            let x = with_strlen(x, strlen);
+            let full_length = x.iter().fold(1_usize, |acc, x| acc.saturating_mul(*x));
+            if full_length == usize::MAX {
+                return Err(error::Error::Overflow);
+            }
+            self.check_sizelen(full_length, &indices, &x, putting.is_some())?;
+            x
+        } else {
+            let full_length = match putting {
+                Some(num_strings) => num_strings
+                    .checked_mul(strlen)
+                    .ok_or(error::Error::Overflow)?,
+                None => self.dimensions.iter().map(Dimension::len).product(),
+            };
+            self.default_sizelen(full_length, &indices, putting.is_some())?
+        };
+        Ok((indices, slice_len))
+    }
 
-        let count = index.iter().map(|_| 1).collect::<Vec<usize>>();
+    /// Get the strings of a char variable, where the last dimension
+    /// holds the characters of each string. `indices` and `slice_len`
+    /// do not include this dimension.
+    ///
+    /// The characters are decoded as given by the `_Encoding` attribute
+    /// (UTF-8 if not present), and the strings are cut at the first NUL
+    /// with trailing spaces removed.
+    ///
+    /// # Errors
+    ///
+    /// [`error::Error::TypeMismatch`] if this is not a char variable, or
+    /// the encoding is not supported
+    pub fn char_strings(
+        &self,
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<Vec<String>> {
+        let encoding = self.char_encoding()?;
+        let (indices, slice_len) = self.char_slab(indices, slice_len, None)?;
+        let strlen = if self.dimensions.is_empty() {
+            1
+        } else {
+            slice_len[slice_len.len() - 1]
+        };
 
+        let mut buf = vec![0_u8; slice_len.iter().product()];
         error::checked(super::with_lock(|| unsafe {
-            nc_get_vara(
+            nc_get_vara_text(
                 self.ncid,
                 self.varid,
-                index.as_ptr(),
-                count.as_ptr(),
-                std::ptr::addr_of_mut!(vlen).cast(),
+                indices.as_ptr(),
+                slice_len.as_ptr(),
+                buf.as_mut_ptr().cast(),
             )
         }))?;
 
-        let mut v = Vec::<T>::with_capacity(vlen.len);
+        if strlen == 0 {
+            return Ok(Vec::new());
+        }
+        Ok(buf.chunks(strlen).map(|x| encoding.decode(x)).collect())
+    }
+
+    /// Get values of an enum variable as the names of the members
+    ///
+    /// # Errors
+    ///
+    /// The variable is not an enum, or a value is not a
+    /// member of the enum (such as an unwritten fill value)
+    pub fn enum_values(
+        &self,
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<Vec<String>> {
+        self.enum_values_with_shape(indices, slice_len)
+            .map(|(values, _)| values)
+    }
 
+    #[cfg(feature = "ndarray")]
+    /// Get values of an enum variable as the names of the members
+    ///
+    /// # Errors
+    ///
+    /// The variable is not an enum, or a value is not a
+    /// member of the enum (such as an unwritten fill value)
+    pub fn enum_values_array(
+        &self,
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<ArrayD<String>> {
+        let (values, shape) = self.enum_values_with_shape(indices, slice_len)?;
+        Ok(ArrayD::from_shape_vec(shape, values).unwrap())
+    }
+
+    fn enum_values_with_shape(
+        &self,
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<(Vec<String>, Vec<usize>)> {
+        let enumtype = match self.vartype() {
+            super::types::VariableType::Enum(e) => e,
+            _ => return Err(error::Error::TypeMismatch),
+        };
+        let (indices, slice_len, full_length) =
+            self.resolve_slab(indices, slice_len, None, false)?;
+
+        let base = enumtype.typ();
+        let members = enumtype.members_i64()?;
+        let mut buf = vec![0_u8; full_length * base.size()];
         unsafe {
-            std::ptr::copy_nonoverlapping(vlen.p as *const T, v.as_mut_ptr(), vlen.len);
-            v.set_len(vlen.len);
+            error::checked(super::with_lock(|| {
+                nc_get_vara(
+                    self.ncid,
+                    self.varid,
+                    indices.as_ptr(),
+                    slice_len.as_ptr(),
+                    buf.as_mut_ptr().cast(),
+                )
+            }))?;
         }
-        error::checked(super::with_lock(|| unsafe { nc_free_vlen(&mut vlen) })).unwrap();
 
-        Ok(v)
+        let values = buf
+            .chunks(base.size())
+            .map(|bytes| {
+                let value = base
+                    .integer_from_ne_bytes(bytes)
+                    .ok_or(error::Error::TypeUnknown(base.id()))?;
+                members
+                    .iter()
+                    .find(|(_, v)| *v == value)
+                    .map(|(name, _)| name.clone())
+                    .ok_or_else(|| {
+                        error::Error::NotFound(format!("enum member with value {}", value))
+                    })
+            })
+            .collect::<error::Result<Vec<_>>>()?;
+        Ok((values, slice_len.to_vec()))
+    }
+
+    /// Checks that the variable is a compound with the layout of `T`
+    fn check_compound<T: NcCompound>(&self) -> error::Result<()> {
+        match self.vartype() {
+            VariableType::Compound(c) => c.check_layout(&T::compound_descriptor()),
+            _ => Err(error::Error::TypeMismatch),
+        }
+    }
+
+    /// Get values of a compound variable as rust structs
+    ///
+    /// # Errors
+    ///
+    /// [`error::Error::TypeMismatch`] if the variable is not a compound
+    /// with the field names, offsets and types of `T`
+    pub fn compound_values<T: NcCompound>(
+        &self,
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<Vec<T>> {
+        self.check_compound::<T>()?;
+        let (indices, slice_len, full_length) =
+            self.resolve_slab(indices, slice_len, None, false)?;
+
+        let mut values = Vec::<T>::with_capacity(full_length);
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_get_vara(
+                    self.ncid,
+                    self.varid,
+                    indices.as_ptr(),
+                    slice_len.as_ptr(),
+                    values.as_mut_ptr().cast(),
+                )
+            }))?;
+            values.set_len(full_length);
+        }
+        Ok(values)
     }
 }
 
@@ -1038,6 +2390,37 @@ impl<'g> VariableMut<'g> {
         Ok(())
     }
 
+    /// Put multiple strings in a string variable in a single call
+    ///
+    /// # Errors
+    ///
+    /// [`error::Error::NulError`] if a string contains interior NUL
+    pub fn put_strings<S: AsRef<str>>(
+        &mut self,
+        values: &[S],
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<()> {
+        let (indices, slice_len, _) =
+            self.resolve_slab(indices, slice_len, Some(values.len()), true)?;
+
+        let values = values
+            .iter()
+            .map(|v| std::ffi::CString::new(v.as_ref()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut ptrs = values.iter().map(|v| v.as_ptr()).collect::<Vec<_>>();
+
+        error::checked(super::with_lock(|| unsafe {
+            nc_put_vara_string(
+                self.ncid,
+                self.varid,
+                indices.as_ptr(),
+                slice_len.as_ptr(),
+                ptrs.as_mut_ptr(),
+            )
+        }))
+    }
+
     /// Put a slice of values at `indices`
     pub fn put_values<T: Numeric>(
         &mut self,
@@ -1165,6 +2548,77 @@ impl<'g> VariableMut<'g> {
         Ok(())
     }
 
+    /// Set the `scale_factor` and `add_offset` attributes used for packing.
+    /// The type of the attributes gives the type of the unpacked data
+    pub fn set_packing<T: Unpacked>(
+        &mut self,
+        scale_factor: T,
+        add_offset: T,
+    ) -> error::Result<()> {
+        self.add_attribute("scale_factor", scale_factor)?;
+        self.add_attribute("add_offset", add_offset)?;
+        Ok(())
+    }
+
+    /// Pack values as given by [`Variable::packing`] and put them in the variable.
+    /// Integer variables get rounded values clipped to the valid range, and
+    /// NaN is stored as `_FillValue`. The valid range is given by `valid_range`,
+    /// `valid_min` and `valid_max`, and otherwise by the range of the type
+    /// without the fill value, so that no value is written as the fill value
+    ///
+    /// # Errors
+    ///
+    /// NaN in the values of a variable without `_FillValue`
+    pub fn put_values_packed<T: Unpacked>(
+        &mut self,
+        values: &[T],
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<()> {
+        let range = match self.vartype() {
+            VariableType::Basic(basic) => basic.integer_range(),
+            _ => return Err(error::Error::TypeMismatch),
+        };
+        let range = match range {
+            Some((mut min, mut max)) => {
+                let mask = self.mask()?;
+                if mask.valid_min.is_some() || mask.valid_max.is_some() {
+                    min = mask.valid_min.map_or(min, |valid| valid.max(min));
+                    max = mask.valid_max.map_or(max, |valid| valid.min(max));
+                } else if let Some(fill_value) = mask.fill_value {
+                    // The default valid range excludes the fill value and
+                    // everything beyond it, as in the netCDF user guide
+                    if fill_value > 0.0 {
+                        max = max.min(fill_value - 1.0);
+                    } else {
+                        min = min.max(fill_value + 1.0);
+                    }
+                }
+                Some((min, max))
+            }
+            None => None,
+        };
+        let packing = self.packing()?;
+        let fill_value = self.attribute_f64("_FillValue")?;
+
+        let packed = values
+            .iter()
+            .map(|x| {
+                let x = x.to_f64();
+                if x.is_nan() {
+                    return fill_value
+                        .ok_or_else(|| "NaN can not be packed without a _FillValue".into());
+                }
+                let x = packing.pack(x);
+                Ok(match range {
+                    Some((min, max)) => x.round().max(min).min(max),
+                    None => x,
+                })
+            })
+            .collect::<error::Result<Vec<f64>>>()?;
+        self.put_values(&packed, indices, slice_len)
+    }
+
     /// Set the fill value to no value. Use this when wanting to avoid
     /// duplicate writes into empty variables.
     ///
@@ -1271,6 +2725,196 @@ impl<'g> VariableMut<'g> {
             )
         }))
     }
+
+    /// Put a single opaque blob at the index
+    ///
+    /// # Errors
+    ///
+    /// [`error::Error::TypeMismatch`] if the variable is not opaque,
+    /// [`error::Error::BufferLen`] if the blob is not of the size of the type
+    pub fn put_opaque(&mut self, value: &[u8], index: &[usize]) -> error::Result<()> {
+        let size = self.opaque_size()?;
+        if value.len() != size {
+            return Err(error::Error::BufferLen(value.len(), size));
+        }
+        self.check_indices(index, true)?;
+
+        let count = index.iter().map(|_| 1).collect::<Vec<usize>>();
+
+        error::checked(super::with_lock(|| unsafe {
+            nc_put_vara(
+                self.ncid,
+                self.varid,
+                index.as_ptr(),
+                count.as_ptr(),
+                value.as_ptr().cast(),
+            )
+        }))
+    }
+
+    /// Put strings in a char variable, where the last dimension holds
+    /// the characters of each string. `indices` and `slice_len` do not
+    /// include this dimension.
+    ///
+    /// The strings are encoded as given by the `_Encoding` attribute
+    /// (UTF-8 if not present), and padded with NUL to the length of
+    /// the dimension. Classic files must have left define mode, see
+    /// [`MutableFile::define`](crate::MutableFile::define)
+    ///
+    /// # Errors
+    ///
+    /// [`error::Error::TypeMismatch`] if this is not a char variable.
+    /// A string which can not be encoded, or is longer than the
+    /// dimension gives an error
+    pub fn put_char_strings<S: AsRef<str>>(
+        &mut self,
+        values: &[S],
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<()> {
+        let encoding = self.char_encoding()?;
+        let (indices, slice_len) = self.char_slab(indices, slice_len, Some(values.len()))?;
+        let strlen = self.dimensions.last().map_or(1, Dimension::len);
+
+        let mut buf = Vec::with_capacity(values.len() * strlen);
+        for value in values {
+            let value = value.as_ref();
+            let mut bytes = encoding.encode(value)?;
+            if bytes.len() > strlen {
+                return Err(format!(
+                    "string {:?} needs {} characters, but the string dimension has length {}",
+                    value,
+                    bytes.len(),
+                    strlen
+                )
+                .into());
+            }
+            bytes.resize(strlen, 0);
+            buf.extend_from_slice(&bytes);
+        }
+
+        error::checked(super::with_lock(|| unsafe {
+            nc_put_vara_text(
+                self.ncid,
+                self.varid,
+                indices.as_ptr(),
+                slice_len.as_ptr(),
+                buf.as_ptr().cast(),
+            )
+        }))
+    }
+
+    /// Put multiple vlen elements in a single call
+    ///
+    /// # Errors
+    ///
+    /// [`error::Error::TypeMismatch`] if the variable is not a vlen of `T`
+    pub fn put_vlens<T: Numeric, V: AsRef<[T]>>(
+        &mut self,
+        values: &[V],
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<()> {
+        self.check_vlen::<T>()?;
+        let (indices, slice_len, _) =
+            self.resolve_slab(indices, slice_len, Some(values.len()), true)?;
+
+        let vlens = values
+            .iter()
+            .map(|v| {
+                let v = v.as_ref();
+                nc_vlen_t {
+                    len: v.len(),
+                    p: v.as_ptr() as *mut _,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        error::checked(super::with_lock(|| unsafe {
+            nc_put_vara(
+                self.ncid,
+                self.varid,
+                indices.as_ptr(),
+                slice_len.as_ptr(),
+                vlens.as_ptr().cast(),
+            )
+        }))
+    }
+
+    /// Put values in an enum variable, given by the names of the members
+    ///
+    /// # Errors
+    ///
+    /// The variable is not an enum, or a name is not a member of the enum
+    pub fn put_enum_names(
+        &mut self,
+        names: &[&str],
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<()> {
+        let enumtype = match self.vartype() {
+            super::types::VariableType::Enum(e) => e,
+            _ => return Err(error::Error::TypeMismatch),
+        };
+        let (indices, slice_len, _) =
+            self.resolve_slab(indices, slice_len, Some(names.len()), true)?;
+
+        let base = enumtype.typ();
+        let members = enumtype.members_i64()?;
+        let mut buf = Vec::with_capacity(names.len() * base.size());
+        for name in names {
+            let value = members
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, v)| *v)
+                .ok_or_else(|| error::Error::NotFound(format!("enum member {}", name)))?;
+            let bytes = base
+                .integer_to_ne_bytes(value)
+                .ok_or(error::Error::TypeUnknown(base.id()))?;
+            buf.extend_from_slice(&bytes);
+        }
+
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_put_vara(
+                    self.ncid,
+                    self.varid,
+                    indices.as_ptr(),
+                    slice_len.as_ptr(),
+                    buf.as_ptr().cast(),
+                )
+            }))
+        }
+    }
+
+    /// Put values in a compound variable from rust structs
+    ///
+    /// # Errors
+    ///
+    /// [`error::Error::TypeMismatch`] if the variable is not a compound
+    /// with the field names, offsets and types of `T`
+    pub fn put_compound_values<T: NcCompound>(
+        &mut self,
+        values: &[T],
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<()> {
+        self.check_compound::<T>()?;
+        let (indices, slice_len, _) =
+            self.resolve_slab(indices, slice_len, Some(values.len()), true)?;
+
+        unsafe {
+            error::checked(super::with_lock(|| {
+                nc_put_vara(
+                    self.ncid,
+                    self.varid,
+                    indices.as_ptr(),
+                    slice_len.as_ptr(),
+                    values.as_ptr().cast(),
+                )
+            }))
+        }
+    }
 }
 
 impl<'g> VariableMut<'g> {
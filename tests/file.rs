@@ -42,3 +42,325 @@ fn appending_with() {
     let _file =
         netcdf::append_with(&path, netcdf::Options::NETCDF4 | netcdf::Options::DISKLESS).unwrap();
 }
+
+#[test]
+fn define_mode() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("define_mode.nc");
+    {
+        let mut file = netcdf::create_with(&path, netcdf::Options::CLASSIC).unwrap();
+        file.define(|file| {
+            file.add_dimension("x", 4)?;
+            for i in 0..10 {
+                file.add_variable::<i32>(&format!("var{}", i), &["x"])?;
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        let mut var = file.variable_mut("var3").unwrap();
+        var.put_values(&[1, 2, 3, 4], None, None).unwrap();
+
+        let padding = netcdf::HeaderPadding {
+            h_minfree: 1024,
+            ..netcdf::HeaderPadding::default()
+        };
+        file.define_with(padding, |file| {
+            file.add_attribute("title", "define mode")?;
+            Ok(())
+        })
+        .unwrap();
+
+        let err = file
+            .define(|file| {
+                file.add_dimension("x", 3)?;
+                Ok(())
+            })
+            .unwrap_err();
+        assert!(matches!(err, netcdf::error::Error::AlreadyExists));
+        // The file must have left define mode after the failure
+        let mut var = file.variable_mut("var4").unwrap();
+        var.put_values(&[5, 6, 7, 8], None, None).unwrap();
+    }
+
+    let file = netcdf::open(&path).unwrap();
+    assert_eq!(file.variables().count(), 10);
+    let mut values = [0; 4];
+    file.variable("var3")
+        .unwrap()
+        .values_to(&mut values, None, None)
+        .unwrap();
+    assert_eq!(values, [1, 2, 3, 4]);
+    file.variable("var4")
+        .unwrap()
+        .values_to(&mut values, None, None)
+        .unwrap();
+    assert_eq!(values, [5, 6, 7, 8]);
+    assert!(file.attribute("title").is_some());
+}
+
+#[test]
+fn sync_close_abort() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("close.nc");
+    let mut file = netcdf::create(&path).unwrap();
+    file.add_dimension("x", 2).unwrap();
+    let mut var = file.add_variable::<u8>("x", &["x"]).unwrap();
+    var.put_values(&[1, 2], None, None).unwrap();
+    file.sync().unwrap();
+    file.close().unwrap();
+
+    let file = netcdf::open(&path).unwrap();
+    assert!(file.variable("x").is_some());
+
+    let path = d.path().join("aborted.nc");
+    let mut file = netcdf::create_with(&path, netcdf::Options::CLASSIC).unwrap();
+    file.add_dimension("x", 2).unwrap();
+    file.abort().unwrap();
+    assert!(!path.exists());
+}
+
+#[test]
+fn file_format() {
+    use netcdf::{Format, FormatExtended, Options};
+    let d = tempfile::tempdir().unwrap();
+
+    for (i, &(options, format, extended)) in [
+        (Options::CLASSIC, Format::Classic, FormatExtended::Nc3),
+        (
+            Options::_64BIT_OFFSET,
+            Format::Offset64,
+            FormatExtended::Nc3,
+        ),
+        (Options::_64BIT_DATA, Format::Cdf5, FormatExtended::Nc3),
+        (Options::NETCDF4, Format::Netcdf4, FormatExtended::Hdf5),
+        (
+            Options::NETCDF4 | Options::CLASSIC,
+            Format::Netcdf4Classic,
+            FormatExtended::Hdf5,
+        ),
+    ]
+    .iter()
+    .enumerate()
+    {
+        let path = d.path().join(format!("format{}.nc", i));
+        let file = netcdf::create_with(&path, options).unwrap();
+        assert_eq!(file.format().unwrap(), format);
+        assert_eq!(file.format_extended().unwrap(), extended);
+    }
+}
+
+#[test]
+fn to_cdl() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("cdl.nc");
+    {
+        let mut file = netcdf::create(&path).unwrap();
+        file.add_attribute("title", "example").unwrap();
+        file.add_dimension("x", 3).unwrap();
+        file.add_unlimited_dimension("time").unwrap();
+        file.add_enum_type::<i8>("color", &[("red", 0), ("blue", 1)])
+            .unwrap();
+        let mut var = file.add_variable::<f32>("temp", &["time", "x"]).unwrap();
+        var.add_attribute("scale", 0.5_f32).unwrap();
+        var.add_attribute("offset", 2_i16).unwrap();
+        var.put_values(&[1.5_f32, 2.0, 3.25, 4.0, 5.0, 6.0], None, Some(&[2, 3]))
+            .unwrap();
+        let mut var = file.add_variable::<u8>("flags", &["x"]).unwrap();
+        var.put_values(&[1_u8, 2, 3], None, None).unwrap();
+        let mut group = file.add_group("sub").unwrap();
+        group.add_variable::<i64>("count", &[]).unwrap();
+    }
+    let file = netcdf::open(&path).unwrap();
+
+    let mut cdl = Vec::new();
+    file.to_cdl(&mut cdl, &netcdf::cdl::CdlOptions::default())
+        .unwrap();
+    let cdl = String::from_utf8(cdl).unwrap();
+    assert!(cdl.starts_with("netcdf cdl {\n"));
+    assert!(cdl.contains("types:\n  byte enum color {red = 0, blue = 1} ;\n"));
+    assert!(cdl.contains("\tx = 3 ;\n"));
+    assert!(cdl.contains("\ttime = UNLIMITED ; // (2 currently)\n"));
+    assert!(cdl.contains("\tfloat temp(time, x) ;\n"));
+    assert!(cdl.contains("\t\ttemp:scale = 0.5f ;\n"));
+    assert!(cdl.contains("\t\ttemp:offset = 2s ;\n"));
+    assert!(cdl.contains("// global attributes:\n\t\t:title = \"example\" ;\n"));
+    assert!(cdl.contains(" temp =\n  1.5, 2, 3.25,\n  4, 5, 6 ;\n"));
+    assert!(cdl.contains(" flags = 1, 2, 3 ;\n"));
+    assert!(cdl.contains("group: sub {\n  variables:\n  \tint64 count ;\n"));
+    assert!(cdl.contains("   count = _ ;\n"));
+    assert!(cdl.contains("  } // group sub\n"));
+    assert!(cdl.ends_with("}\n"));
+
+    let options = netcdf::cdl::CdlOptions {
+        header_only: false,
+        variables: Some(vec!["flags".to_string()]),
+    };
+    let mut cdl = Vec::new();
+    file.to_cdl(&mut cdl, &options).unwrap();
+    let cdl = String::from_utf8(cdl).unwrap();
+    assert!(cdl.contains("\tfloat temp(time, x) ;\n"));
+    assert!(cdl.contains(" flags = 1, 2, 3 ;\n"));
+    assert!(!cdl.contains(" temp ="));
+
+    let options = netcdf::cdl::CdlOptions {
+        header_only: true,
+        variables: None,
+    };
+    let mut cdl = Vec::new();
+    file.to_cdl(&mut cdl, &options).unwrap();
+    let cdl = String::from_utf8(cdl).unwrap();
+    assert!(!cdl.contains("data:"));
+}
+
+#[test]
+fn cdl_roundtrip() {
+    let cdl = r#"netcdf roundtrip {
+types:
+  ubyte enum color {red = 0, green = 1, blue = 2} ;
+  int(*) ragged ;
+  opaque(4) blob ;
+  compound point {
+    float x ;
+    double y(2) ;
+    string label ;
+  }; // point
+dimensions:
+	time = UNLIMITED ; // (2 currently)
+	x = 3 ;
+	len = 4 ;
+variables:
+	short temp(time, x) ;
+		temp:_FillValue = -1s ;
+		temp:scale = 0.5f ;
+		temp:valid = 1L, 10L ;
+	char code(x, len) ;
+	color colors(x) ;
+	ragged lists(time) ;
+	blob blobs(time) ;
+	point points(x) ;
+	string names(x) ;
+
+// global attributes:
+		:title = "line one\n",
+			"line two" ;
+		string :keywords = "a", "b" ;
+data:
+
+ temp =
+  1, 2, _,
+  4, 5, 6 ;
+
+ code =
+  "ab",
+  "cdef",
+  "" ;
+
+ colors = red, blue, green ;
+
+ lists = {1, 2, 3}, {} ;
+
+ blobs = 0X01020304, 0XFF000000 ;
+
+ points = {1.5, {2, 3}, "one"}, {0, {0, 0}, "two"}, {-1, {1e+20, -2.5}, "c"} ;
+
+ names = "first", "second", "" ;
+
+group: sub {
+  dimensions:
+  	y = 2 ;
+  variables:
+  	int64 count(y) ;
+  	double scalar ;
+  data:
+
+   count = 10, 20 ;
+
+   scalar = 3.25 ;
+  } // group sub
+}
+"#;
+
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("roundtrip.nc");
+    let mut file = netcdf::create(&path).unwrap();
+    netcdf::cdl::parse(cdl, &mut file).unwrap();
+    let mut output = Vec::new();
+    file.to_cdl(&mut output, &netcdf::cdl::CdlOptions::default())
+        .unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(output, cdl);
+
+    let temp = file.variable("temp").unwrap();
+    let mut values = [0_i16; 6];
+    temp.values_to(&mut values, None, None).unwrap();
+    assert_eq!(values, [1, 2, -1, 4, 5, 6]);
+    assert_eq!(
+        file.attribute("title").unwrap().value().unwrap(),
+        netcdf::AttrValue::Str("line one\nline two".to_string())
+    );
+
+    let err = netcdf::cdl::parse(
+        "netcdf bad {\ndimensions:\n\tx = ;\n}",
+        &mut netcdf::create(d.path().join("bad.nc")).unwrap(),
+    )
+    .unwrap_err();
+    assert!(err.to_string().starts_with("line 3:"));
+}
+
+#[test]
+fn cdl_chunk_sizes() {
+    let cdl = |sizes: &str| {
+        format!(
+            "netcdf chunks {{\ndimensions:\n\tx = 4 ;\n\ty = 2 ;\nvariables:\n\tint v(x, y) ;\n\t\tv:_ChunkSizes = {} ;\n}}",
+            sizes
+        )
+    };
+    let d = tempfile::tempdir().unwrap();
+
+    let mut file = netcdf::create(d.path().join("chunks.nc")).unwrap();
+    netcdf::cdl::parse(&cdl("2, 1"), &mut file).unwrap();
+    let var = file.variable("v").unwrap();
+    assert_eq!(var.storage().unwrap(), netcdf::Storage::Chunked(vec![2, 1]));
+
+    for (i, sizes) in ["2", "2, 1, 1"].iter().enumerate() {
+        let path = d.path().join(format!("bad_chunks{}.nc", i));
+        let mut file = netcdf::create(path).unwrap();
+        let err = netcdf::cdl::parse(&cdl(sizes), &mut file).unwrap_err();
+        assert!(err.to_string().contains("_ChunkSizes"));
+    }
+}
+
+#[test]
+fn cdl_escapes() {
+    let cdl = "netcdf escapes {\ndimensions:\n\tlen = 4 ;\nvariables:\n\tchar c(len) ;\ndata:\n\n c = \"a\\377\\x01\\n\" ;\n}";
+    let d = tempfile::tempdir().unwrap();
+    let mut file = netcdf::create(d.path().join("escapes.nc")).unwrap();
+    netcdf::cdl::parse(cdl, &mut file).unwrap();
+
+    let var = file.variable("c").unwrap();
+    let mut bytes = [0_u8; 4];
+    var.raw_values(&mut bytes, &[0], &[4]).unwrap();
+    assert_eq!(bytes, [b'a', 0xff, 0x01, b'\n']);
+}
+
+#[test]
+fn cdl_opaque_length() {
+    let cdl = |value: &str| {
+        format!(
+            "netcdf blobs {{\ntypes:\n  opaque(2) blob ;\nvariables:\n\tblob b ;\ndata:\n\n b = {} ;\n}}",
+            value
+        )
+    };
+    let d = tempfile::tempdir().unwrap();
+
+    let mut file = netcdf::create(d.path().join("blobs.nc")).unwrap();
+    netcdf::cdl::parse(&cdl("0X01"), &mut file).unwrap();
+    let var = file.variable("b").unwrap();
+    let mut bytes = [0xff_u8; 2];
+    var.raw_values(&mut bytes, &[], &[]).unwrap();
+    assert_eq!(bytes, [0x01, 0x00]);
+
+    let mut file = netcdf::create(d.path().join("long_blobs.nc")).unwrap();
+    netcdf::cdl::parse(&cdl("0X010203"), &mut file).unwrap_err();
+}
@@ -73,3 +73,25 @@ fn find_variable() {
         }
     }
 }
+
+#[test]
+fn rename_group() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("rename_group.nc");
+    let mut file = netcdf::create(path).unwrap();
+    file.add_group("taken").unwrap();
+    let mut group = file.add_group("group").unwrap();
+    group.add_dimension("d", 3).unwrap();
+    group.add_attribute("a", 1).unwrap();
+    group.rename("renamed").unwrap();
+    group.rename_dimension("d", "e").unwrap();
+    group.rename_attribute("a", "b").unwrap();
+    let err = group.rename("taken").unwrap_err();
+    assert!(matches!(err, netcdf::error::Error::AlreadyExists));
+
+    let group = file.group("renamed").unwrap().unwrap();
+    assert_eq!(group.name(), "renamed");
+    assert!(group.dimension("e").is_some());
+    assert!(group.attribute("b").is_some());
+    assert!(file.group("group").unwrap().is_none());
+}
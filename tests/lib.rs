@@ -815,6 +815,221 @@ fn more_fill_values() {
     // assert!(var.attribute("_FillValue").is_none());
 }
 
+#[test]
+fn effective_fill_values() {
+    use netcdf::Numeric;
+    assert_eq!(i8::DEFAULT_FILL, -127);
+    assert_eq!(u8::DEFAULT_FILL, 255);
+    assert_eq!(i16::DEFAULT_FILL, -32767);
+    assert_eq!(u64::DEFAULT_FILL, 18446744073709551614);
+    assert_eq!(f64::DEFAULT_FILL, 9.969209968386869e36);
+
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("effective_fill_values.nc");
+    let mut file = netcdf::create(path).unwrap();
+    file.add_dimension("x", 2).unwrap();
+
+    let var = &mut file.add_variable::<i16>("v0", &["x"]).unwrap();
+    var.put_value(6_i16, Some(&[1])).unwrap();
+    assert_eq!(
+        var.effective_fill_value::<i16>().unwrap(),
+        Some(i16::DEFAULT_FILL)
+    );
+    assert_eq!(var.value::<i16>(Some(&[0])).unwrap(), i16::DEFAULT_FILL);
+    assert!(var.effective_fill_value::<i32>().is_err());
+
+    let var = &mut file.add_variable::<f32>("v1", &["x"]).unwrap();
+    var.set_fill_value(3.0_f32).unwrap();
+    assert_eq!(var.effective_fill_value::<f32>().unwrap(), Some(3.0));
+
+    let var = &mut file.add_variable::<f32>("v2", &["x"]).unwrap();
+    unsafe {
+        var.set_nofill().unwrap();
+    }
+    assert_eq!(var.effective_fill_value::<f32>().unwrap(), None);
+}
+
+#[test]
+fn fill_mode() {
+    use netcdf::{FillMode, Numeric};
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("fill_mode.nc");
+    let mut file = netcdf::create(path).unwrap();
+
+    unsafe {
+        assert_eq!(
+            file.set_fill_mode(FillMode::NoFill).unwrap(),
+            FillMode::Fill
+        );
+        assert_eq!(
+            file.set_fill_mode(FillMode::NoFill).unwrap(),
+            FillMode::NoFill
+        );
+        assert_eq!(
+            file.set_fill_mode(FillMode::Fill).unwrap(),
+            FillMode::NoFill
+        );
+    }
+
+    file.add_dimension("x", 2).unwrap();
+    let var = &mut file.add_variable::<i32>("v", &["x"]).unwrap();
+    assert_eq!(
+        var.effective_fill_value::<i32>().unwrap(),
+        Some(i32::DEFAULT_FILL)
+    );
+    unsafe {
+        var.set_nofill().unwrap();
+    }
+    assert_eq!(var.effective_fill_value::<i32>().unwrap(), None);
+    var.put_values(&[1, 2], None, None).unwrap();
+    assert_eq!(var.value::<i32>(Some(&[1])).unwrap(), 2);
+}
+
+#[test]
+fn packed_values() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("packed_values.nc");
+    {
+        let mut file = netcdf::create(&path).unwrap();
+        file.add_dimension("x", 4).unwrap();
+        file.add_dimension("y", 2).unwrap();
+
+        let var = &mut file.add_variable::<i16>("t", &["x"]).unwrap();
+        var.set_fill_value(i16::MIN).unwrap();
+        var.set_packing(0.01_f32, 273.15_f32).unwrap();
+        var.put_values_packed(&[273.15_f32, 300.0, f32::NAN, 1e6], None, None)
+            .unwrap();
+
+        let var = &mut file.add_variable::<u16>("clipped", &["y"]).unwrap();
+        var.set_packing(1.0, 0.0).unwrap();
+        // Clipped to the default valid range below the fill value
+        var.put_values_packed(&[1e6, -1e6], None, None).unwrap();
+
+        let var = &mut file.add_variable::<i64>("ranged", &["y"]).unwrap();
+        var.add_attribute("valid_range", vec![-10_i64, 10]).unwrap();
+        var.set_packing(1.0, 0.0).unwrap();
+        var.put_values_packed(&[1e30, -1e30], None, None).unwrap();
+
+        let var = &mut file.add_variable::<i64>("large", &["y"]).unwrap();
+        var.set_fill_value(0_i64).unwrap();
+        var.set_packing(1.0, 0.0).unwrap();
+        var.put_values_packed(&[1e30, -1e30], None, None).unwrap();
+
+        let var = &mut file.add_variable::<i16>("masked", &["x"]).unwrap();
+        var.add_attribute("missing_value", -1_i16).unwrap();
+        var.add_attribute("valid_max", 100_i16).unwrap();
+        var.set_packing(2.0_f32, 0.0_f32).unwrap();
+        var.put_values(&[-1_i16, 50, 200, -32767], None, None)
+            .unwrap();
+
+        let var = &mut file.add_variable::<f32>("f", &["x"]).unwrap();
+        var.put_values_packed(&[0.5_f64, 1.5, 2.5, 3.5], None, None)
+            .unwrap();
+        var.put_values_packed(&[f64::NAN], Some(&[0]), Some(&[1]))
+            .unwrap_err();
+    }
+    let file = netcdf::open(&path).unwrap();
+
+    let var = &file.variable("t").unwrap();
+    let packing = var.packing().unwrap();
+    assert_eq!(packing.scale_factor, f64::from(0.01_f32));
+    assert_eq!(packing.add_offset, f64::from(273.15_f32));
+    let mut raw = [0_i16; 4];
+    var.values_to(&mut raw, None, None).unwrap();
+    assert_eq!(raw, [0, 2685, i16::MIN, i16::MAX]);
+
+    let mut values = [0.0_f64; 4];
+    var.values_decoded_to(&mut values, None, None).unwrap();
+    assert!((values[0] - 273.15).abs() < 1e-4);
+    assert!((values[1] - 300.0).abs() < 1e-4);
+    assert!(values[2].is_nan());
+    assert!((values[3] - 600.82).abs() < 1e-4);
+
+    let var = &file.variable("f").unwrap();
+    assert_eq!(
+        var.values_decoded::<f32>(None, None)
+            .unwrap()
+            .as_slice()
+            .unwrap(),
+        &[0.5, 1.5, 2.5, 3.5]
+    );
+
+    // Missing, out of range and default fill values are masked
+    let var = &file.variable("masked").unwrap();
+    let mut values = [0.0_f32; 4];
+    var.values_decoded_to(&mut values, None, None).unwrap();
+    assert!(values[0].is_nan());
+    assert_eq!(values[1], 100.0);
+    assert!(values[2].is_nan());
+    assert!(values[3].is_nan());
+
+    let mut raw = [0_u16; 2];
+    let var = &file.variable("clipped").unwrap();
+    var.values_to(&mut raw, None, None).unwrap();
+    assert_eq!(raw, [u16::MAX - 1, 0]);
+
+    let mut raw = [0_i64; 2];
+    let var = &file.variable("ranged").unwrap();
+    var.values_to(&mut raw, None, None).unwrap();
+    assert_eq!(raw, [10, -10]);
+
+    let var = &file.variable("large").unwrap();
+    var.values_to(&mut raw, None, None).unwrap();
+    assert!(raw[0] > i64::MAX - 2048);
+    assert_eq!(raw[1], 1);
+}
+
+#[test]
+fn masked_values() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("masked_values.nc");
+    {
+        let mut file = netcdf::create(&path).unwrap();
+        file.add_dimension("x", 6).unwrap();
+        file.add_dimension("y", 4).unwrap();
+        file.add_dimension("z", 2).unwrap();
+
+        let var = &mut file.add_variable::<i16>("a", &["x"]).unwrap();
+        var.set_fill_value(-1_i16).unwrap();
+        var.add_attribute("missing_value", vec![-2_i16, -3])
+            .unwrap();
+        var.add_attribute("valid_range", vec![0_i16, 100]).unwrap();
+        var.put_values(&[-1_i16, -2, -3, 5, 150, 100], None, None)
+            .unwrap();
+
+        let var = &mut file.add_variable::<f32>("b", &["y"]).unwrap();
+        var.add_attribute("valid_min", 0.0_f32).unwrap();
+        var.put_values(&[1.5_f32, -1.0], Some(&[0]), Some(&[2]))
+            .unwrap();
+
+        let var = &mut file.add_variable::<u8>("c", &["z"]).unwrap();
+        var.put_value(7_u8, Some(&[0])).unwrap();
+    }
+    let file = netcdf::open(&path).unwrap();
+
+    let var = &file.variable("a").unwrap();
+    let mut values = [None; 6];
+    var.values_masked_to::<i16>(&mut values, None, None)
+        .unwrap();
+    assert_eq!(values, [None, None, None, Some(5), None, Some(100)]);
+
+    let var = &file.variable("b").unwrap();
+    let mut values = [None; 4];
+    var.values_masked_to::<f32>(&mut values, None, None)
+        .unwrap();
+    assert_eq!(values, [Some(1.5), None, None, None]);
+    #[cfg(feature = "ndarray")]
+    {
+        let values = var.values_masked::<f64>(Some(&[0]), Some(&[2])).unwrap();
+        assert_eq!(values.as_slice().unwrap(), &[Some(1.5), None]);
+    }
+
+    let var = &file.variable("c").unwrap();
+    let mut values = [None; 2];
+    var.values_masked_to::<u8>(&mut values, None, None).unwrap();
+    assert_eq!(values, [Some(7), Some(255)]);
+}
+
 #[test]
 /// Test reading a slice of a variable into a buffer
 fn read_slice_into_buffer() {
@@ -886,6 +1101,507 @@ fn use_compression_chunking() {
     var.chunking(&[100, 2]).unwrap();
 }
 
+#[test]
+fn query_storage_settings() {
+    use netcdf::{CompressionSettings, Storage};
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("query_storage_settings.nc");
+    {
+        let mut file = netcdf::create(&path).unwrap();
+        file.add_dimension("x", 10).unwrap();
+        file.add_unlimited_dimension("t").unwrap();
+
+        let var = &mut file.add_variable::<i32>("compressed", &["x"]).unwrap();
+        var.compression(5).unwrap();
+        var.chunking(&[5]).unwrap();
+
+        file.add_variable::<i32>("contiguous", &["x"]).unwrap();
+        file.add_variable::<i32>("unlimited", &["t", "x"]).unwrap();
+    }
+
+    let file = netcdf::open(&path).unwrap();
+    let var = file.variable("compressed").unwrap();
+    assert_eq!(var.storage().unwrap(), Storage::Chunked(vec![5]));
+    assert_eq!(
+        var.compression_settings().unwrap(),
+        CompressionSettings {
+            shuffle: false,
+            deflate_level: Some(5),
+            fletcher32: false,
+        }
+    );
+
+    let var = file.variable("contiguous").unwrap();
+    assert_eq!(var.storage().unwrap(), Storage::Contiguous);
+    assert_eq!(
+        var.compression_settings().unwrap(),
+        CompressionSettings {
+            shuffle: false,
+            deflate_level: None,
+            fletcher32: false,
+        }
+    );
+
+    let var = file.variable("unlimited").unwrap();
+    assert!(matches!(var.storage().unwrap(), Storage::Chunked(_)));
+
+    let path = d.path().join("query_storage_settings_classic.nc");
+    let mut file = netcdf::create_with(&path, netcdf::Options::CLASSIC).unwrap();
+    file.add_dimension("x", 10).unwrap();
+    let var = file.add_variable::<i32>("x", &["x"]).unwrap();
+    assert_eq!(var.storage().unwrap(), Storage::Contiguous);
+}
+
+#[test]
+fn set_storage_layout() {
+    use netcdf::Storage;
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("set_storage_layout.nc");
+    {
+        let mut file = netcdf::create(&path).unwrap();
+        file.add_dimension("x", 10).unwrap();
+        file.add_dimension("big", 100_000).unwrap();
+
+        let var = &mut file.add_variable::<f64>("compact", &["x"]).unwrap();
+        var.set_storage(Storage::Compact).unwrap();
+        var.put_values(&[1.0; 10], None, None).unwrap();
+
+        let var = &mut file.add_variable::<f64>("contiguous", &["x"]).unwrap();
+        var.set_storage(Storage::Chunked(vec![5])).unwrap();
+        var.set_storage(Storage::Contiguous).unwrap();
+
+        let var = &mut file.add_variable::<u8>("chunked", &["x"]).unwrap();
+        var.set_storage(Storage::Chunked(vec![2])).unwrap();
+
+        let var = &mut file.add_variable::<f64>("too_big", &["big"]).unwrap();
+        let err = var.set_storage(Storage::Compact).unwrap_err();
+        assert!(matches!(err, netcdf::error::Error::Netcdf(_)));
+    }
+
+    let file = netcdf::open(&path).unwrap();
+    let var = file.variable("compact").unwrap();
+    assert_eq!(var.storage().unwrap(), Storage::Compact);
+    let mut values = [0.0; 10];
+    var.values_to(&mut values, None, None).unwrap();
+    assert_eq!(values, [1.0; 10]);
+    let var = file.variable("contiguous").unwrap();
+    assert_eq!(var.storage().unwrap(), Storage::Contiguous);
+    let var = file.variable("chunked").unwrap();
+    assert_eq!(var.storage().unwrap(), Storage::Chunked(vec![2]));
+}
+
+#[test]
+fn chunk_cache() {
+    use netcdf::ChunkCache;
+    let default = netcdf::chunk_cache().unwrap();
+    let cache = ChunkCache {
+        size: 32 * 1024 * 1024,
+        nelems: 1009,
+        preemption: 0.5,
+    };
+    netcdf::set_chunk_cache(cache).unwrap();
+    assert_eq!(netcdf::chunk_cache().unwrap(), cache);
+    netcdf::set_chunk_cache(default).unwrap();
+
+    let file = netcdf::open(test_location().join("simple_nc4.nc")).unwrap();
+    let group = file.group("grp1").unwrap().unwrap();
+    let var = group.variable("data").unwrap();
+    let cache = ChunkCache {
+        size: 1024,
+        nelems: 7,
+        preemption: 1.0,
+    };
+    var.set_chunk_cache(cache).unwrap();
+    assert_eq!(var.chunk_cache().unwrap(), cache);
+
+    let invalid = ChunkCache {
+        preemption: 2.0,
+        ..cache
+    };
+    var.set_chunk_cache(invalid).unwrap_err();
+}
+
+#[test]
+fn shuffle_and_checksum() {
+    use netcdf::{CompressionSettings, DeflateOptions};
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("shuffle_and_checksum.nc");
+    {
+        let mut file = netcdf::create(&path).unwrap();
+        file.add_dimension("x", 10).unwrap();
+
+        let var = &mut file.add_variable::<i32>("deflated", &["x"]).unwrap();
+        var.deflate(DeflateOptions {
+            level: 3,
+            shuffle: true,
+        })
+        .unwrap();
+        var.fletcher32(true).unwrap();
+
+        let var = &mut file.add_variable::<i32>("shuffled", &["x"]).unwrap();
+        var.shuffle(true).unwrap();
+
+        let var = &mut file.add_variable::<i32>("unshuffled", &["x"]).unwrap();
+        var.compression(4).unwrap();
+        var.shuffle(true).unwrap();
+        var.shuffle(false).unwrap();
+        var.put_values(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10], None, None)
+            .unwrap();
+    }
+
+    let file = netcdf::open(&path).unwrap();
+    let var = file.variable("deflated").unwrap();
+    assert_eq!(
+        var.compression_settings().unwrap(),
+        CompressionSettings {
+            shuffle: true,
+            deflate_level: Some(3),
+            fletcher32: true,
+        }
+    );
+    let var = file.variable("shuffled").unwrap();
+    assert_eq!(
+        var.compression_settings().unwrap(),
+        CompressionSettings {
+            shuffle: true,
+            deflate_level: None,
+            fletcher32: false,
+        }
+    );
+    let var = file.variable("unshuffled").unwrap();
+    assert_eq!(
+        var.compression_settings().unwrap(),
+        CompressionSettings {
+            shuffle: false,
+            deflate_level: Some(4),
+            fletcher32: false,
+        }
+    );
+}
+
+#[test]
+fn rename() {
+    let d = tempfile::tempdir().unwrap();
+    for &options in &[netcdf::Options::NETCDF4, netcdf::Options::CLASSIC] {
+        let path = d.path().join(format!("rename{}.nc", options.bits()));
+        {
+            let mut file = netcdf::create_with(&path, options).unwrap();
+            file.add_dimension("x", 3).unwrap();
+            file.add_attribute("title", "rename").unwrap();
+            let mut var = file.add_variable::<i32>("temp", &["x"]).unwrap();
+            var.add_attribute("unit", "K").unwrap();
+            file.add_variable::<i32>("taken", &["x"]).unwrap();
+            file.define(|_| Ok(())).unwrap();
+
+            // In data mode for classic files
+            let mut var = file.variable_mut("temp").unwrap();
+            var.put_values(&[1, 2, 3], None, None).unwrap();
+            var.rename("air_temperature").unwrap();
+            var.rename_attribute("unit", "units").unwrap();
+            let err = var.rename("taken").unwrap_err();
+            assert!(matches!(err, netcdf::error::Error::AlreadyExists));
+            let err = var.rename_attribute("unit", "u").unwrap_err();
+            assert!(matches!(err, netcdf::error::Error::NotFound(_)));
+
+            file.rename_dimension("x", "longitude").unwrap();
+            file.rename_attribute("title", "long_title").unwrap();
+            let err = file.rename_dimension("y", "z").unwrap_err();
+            assert!(matches!(err, netcdf::error::Error::NotFound(_)));
+        }
+
+        let file = netcdf::open(&path).unwrap();
+        assert!(file.variable("temp").is_none());
+        let var = file.variable("air_temperature").unwrap();
+        assert!(var.attribute("units").is_some());
+        assert_eq!(var.dimensions()[0].name(), "longitude");
+        let mut values = [0; 3];
+        var.values_to(&mut values, None, None).unwrap();
+        assert_eq!(values, [1, 2, 3]);
+        assert!(file.attribute("long_title").is_some());
+        assert!(file.dimension("x").is_none());
+    }
+}
+
+#[test]
+fn remove_and_copy_attributes() {
+    use netcdf::AttrValue;
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("copy_attributes_src.nc");
+    let mut src = netcdf::create(&path).unwrap();
+    src.add_attribute("short", 4_i16).unwrap();
+    src.add_attribute("removed", "gone").unwrap();
+    src.add_dimension("x", 1).unwrap();
+    let mut var = src.add_variable::<i32>("var", &["x"]).unwrap();
+    var.add_attribute("units", "m").unwrap();
+    var.add_attribute("removed", 1.0).unwrap();
+    var.remove_attribute("removed").unwrap();
+    let err = var.remove_attribute("removed").unwrap_err();
+    assert!(matches!(err, netcdf::error::Error::NotFound(_)));
+    src.remove_attribute("removed").unwrap();
+    assert!(src.attribute("removed").is_none());
+    let mut group = src.add_group("g").unwrap();
+    group.add_attribute("removed", 1).unwrap();
+    group.remove_attribute("removed").unwrap();
+    assert!(group.attribute("removed").is_none());
+
+    for &options in &[netcdf::Options::NETCDF4, netcdf::Options::CLASSIC] {
+        let path = d
+            .path()
+            .join(format!("copy_attributes_dst{}.nc", options.bits()));
+        let mut dst = netcdf::create_with(&path, options).unwrap();
+        dst.add_dimension("y", 2).unwrap();
+        dst.add_variable::<f32>("other", &["y"]).unwrap();
+        // Leave define mode for classic files
+        dst.define(|_| Ok(())).unwrap();
+
+        let attr = src.attribute("short").unwrap();
+        attr.copy_attribute_to_file(&mut dst).unwrap();
+        let attr = src.variable("var").unwrap();
+        let attr = attr.attribute("units").unwrap();
+        attr.copy_attribute_to(&mut dst.variable_mut("other").unwrap())
+            .unwrap();
+
+        assert_eq!(
+            dst.attribute("short").unwrap().value().unwrap(),
+            AttrValue::Short(4)
+        );
+        assert_eq!(
+            dst.variable("other")
+                .unwrap()
+                .attribute("units")
+                .unwrap()
+                .value()
+                .unwrap(),
+            AttrValue::Str("m".to_string())
+        );
+    }
+
+    let path = d.path().join("copy_attributes_group.nc");
+    let mut dst = netcdf::create(&path).unwrap();
+    let mut group = dst.add_group("g").unwrap();
+    let attr = src.attribute("short").unwrap();
+    attr.copy_attribute_to_group(&mut group).unwrap();
+    assert_eq!(
+        group.attribute("short").unwrap().value().unwrap(),
+        AttrValue::Short(4)
+    );
+}
+
+#[test]
+fn copy_file() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("copy_file_src.nc");
+    {
+        let mut file = netcdf::create(&path).unwrap();
+        file.add_attribute("title", "copy").unwrap();
+        file.add_dimension("x", 6).unwrap();
+        file.add_unlimited_dimension("time").unwrap();
+        file.add_vlen_type::<i32>("vlen").unwrap();
+        let mut var = file.add_variable::<f64>("data", &["time", "x"]).unwrap();
+        var.add_attribute("units", "m").unwrap();
+        var.compression(3).unwrap();
+        let values = (0..24).map(f64::from).collect::<Vec<_>>();
+        var.put_values(&values, None, Some(&[4, 6])).unwrap();
+        let mut var = file.add_string_variable("names", &["x"]).unwrap();
+        var.put_string("first", Some(&[0])).unwrap();
+        var.put_string("last", Some(&[5])).unwrap();
+
+        let mut group = file.add_group("g").unwrap();
+        group.add_dimension("y", 2).unwrap();
+        let mut var = group.add_variable::<i16>("inner", &["y", "x"]).unwrap();
+        var.put_values(&[1_i16; 12], None, None).unwrap();
+    }
+    let src = netcdf::open(&path).unwrap();
+
+    let path = d.path().join("copy_file_dst.nc");
+    let mut dst = netcdf::create(&path).unwrap();
+    let mut options = netcdf::copy::CopyOptions::default();
+    options.chunking.insert("x".to_string(), 3);
+    options.chunking.insert("time".to_string(), 1);
+    // Force the data to be copied in several parts
+    options.buffer_size = 40;
+    netcdf::copy::copy_file(&src, &mut dst, &options).unwrap();
+
+    assert_eq!(
+        dst.attribute("title").unwrap().value().unwrap(),
+        netcdf::AttrValue::Str("copy".to_string())
+    );
+    assert!(dst.dimension("time").unwrap().is_unlimited());
+    assert_eq!(dst.types().unwrap().count(), 1);
+    let var = dst.variable("data").unwrap();
+    assert_eq!(var.storage().unwrap(), netcdf::Storage::Chunked(vec![1, 3]));
+    assert_eq!(var.compression_settings().unwrap().deflate_level, Some(3));
+    assert!(var.attribute("units").is_some());
+    let mut values = [0.0_f64; 24];
+    var.values_to(&mut values, None, None).unwrap();
+    assert_eq!(values[..], (0..24).map(f64::from).collect::<Vec<_>>()[..]);
+    let var = dst.variable("names").unwrap();
+    assert_eq!(var.string_value(Some(&[0])).unwrap(), "first");
+    assert_eq!(var.string_value(Some(&[5])).unwrap(), "last");
+
+    let group = dst.group("g").unwrap().unwrap();
+    let var = group.variable("inner").unwrap();
+    let mut values = [0_i16; 12];
+    var.values_to(&mut values, None, None).unwrap();
+    assert_eq!(values, [1; 12]);
+}
+
+#[test]
+fn copy_variable() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("copy_variable_src.nc");
+    let mut src = netcdf::create_with(&path, netcdf::Options::CLASSIC).unwrap();
+    src.add_dimension("x", 5).unwrap();
+    let mut var = src.add_variable::<i32>("var", &["x"]).unwrap();
+    var.add_attribute("units", "s").unwrap();
+    // Leave define mode for classic files
+    src.define(|_| Ok(())).unwrap();
+    let mut var = src.variable_mut("var").unwrap();
+    var.put_values(&[1, 2, 3, 4, 5], None, None).unwrap();
+
+    let path = d.path().join("copy_variable_dst.nc");
+    let mut dst = netcdf::create(&path).unwrap();
+    dst.add_dimension("x", 5).unwrap();
+    let options = netcdf::copy::CopyOptions {
+        deflate: Some(netcdf::DeflateOptions {
+            level: 1,
+            shuffle: true,
+        }),
+        ..Default::default()
+    };
+    let var = src.variable("var").unwrap();
+    netcdf::copy::copy_variable(&var, &mut dst, &options).unwrap();
+    let err = netcdf::copy::copy_variable(&var, &mut dst, &options).unwrap_err();
+    assert!(matches!(err, netcdf::error::Error::AlreadyExists));
+
+    let var = dst.variable("var").unwrap();
+    let settings = var.compression_settings().unwrap();
+    assert_eq!(settings.deflate_level, Some(1));
+    assert!(settings.shuffle);
+    let mut values = [0; 5];
+    var.values_to(&mut values, None, None).unwrap();
+    assert_eq!(values, [1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn copy_variable_mismatch() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("copy_variable_mismatch_src.nc");
+    let mut src = netcdf::create(&path).unwrap();
+    src.add_dimension("x", 5).unwrap();
+    let blob = src.add_opaque_type("blob", 4).unwrap();
+    src.add_variable_with_type("blob", &[], &blob.into())
+        .unwrap();
+    src.add_variable::<i32>("ints", &["x"]).unwrap();
+
+    let path = d.path().join("copy_variable_mismatch_dst.nc");
+    let mut dst = netcdf::create(&path).unwrap();
+    dst.add_dimension("x", 3).unwrap();
+    dst.add_opaque_type("blob", 8).unwrap();
+    let options = netcdf::copy::CopyOptions::default();
+
+    // Same name, but another size
+    let var = src.variable("blob").unwrap();
+    let err = netcdf::copy::copy_variable(&var, &mut dst, &options).unwrap_err();
+    assert!(matches!(err, netcdf::error::Error::TypeMismatch));
+    assert!(dst.variable("blob").is_none());
+
+    // Dimension shorter than in the source
+    let var = src.variable("ints").unwrap();
+    let err = netcdf::copy::copy_variable(&var, &mut dst, &options).unwrap_err();
+    assert!(matches!(err, netcdf::error::Error::Str(_)));
+    assert!(dst.variable("ints").is_none());
+}
+
+#[test]
+fn use_filters() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("filtered_var.nc");
+    {
+        let mut file = netcdf::create(&path).unwrap();
+        file.add_dimension("x", 10).unwrap();
+
+        let mut var = file.add_variable::<i32>("filtered", &["x"]).unwrap();
+        assert!(var.filters().unwrap().is_empty());
+        // deflate is always available
+        var.add_filter(&netcdf::Filter::new(1, &[4])).unwrap();
+        var.put_values(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10], None, None)
+            .unwrap();
+
+        let mut var = file.add_variable::<i32>("unfiltered", &["x"]).unwrap();
+        let err = var
+            .add_filter(&netcdf::Filter::new(65535, &[]))
+            .unwrap_err();
+        assert!(matches!(err, netcdf::error::Error::Netcdf(_)));
+    }
+
+    let file = netcdf::open(&path).unwrap();
+    let var = file.variable("filtered").unwrap();
+    let filters = var.filters().unwrap();
+    assert_eq!(filters.len(), 1);
+    assert_eq!(filters[0].id, 1);
+    assert_eq!(filters[0].params, [4]);
+    let mut values = [0_i32; 10];
+    var.values_to(&mut values, None, None).unwrap();
+    assert_eq!(values, [1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+
+    let var = file.variable("unfiltered").unwrap();
+    assert!(var.filters().unwrap().is_empty());
+}
+
+#[test]
+#[cfg(feature = "4.8.1")]
+fn quantize() {
+    use netcdf::Quantization;
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("quantize.nc");
+    {
+        let mut file = netcdf::create(&path).unwrap();
+        file.add_dimension("x", 3).unwrap();
+
+        let mut var = file.add_variable::<f64>("q", &["x"]).unwrap();
+        assert_eq!(var.quantization().unwrap(), None);
+        var.quantize(Quantization::BitGroom, 3).unwrap();
+        var.put_values(&[1.0, 0.123456789, 1234.5678], None, None)
+            .unwrap();
+
+        let mut var = file.add_variable::<i32>("i", &["x"]).unwrap();
+        let err = var.quantize(Quantization::BitGroom, 3).unwrap_err();
+        assert!(matches!(err, netcdf::error::Error::TypeMismatch));
+    }
+
+    let file = netcdf::open(&path).unwrap();
+    let var = file.variable("q").unwrap();
+    assert_eq!(
+        var.quantization().unwrap(),
+        Some((Quantization::BitGroom, 3))
+    );
+    let mut values = [0.0_f64; 3];
+    var.values_to(&mut values, None, None).unwrap();
+    assert!((values[1] - 0.123456789).abs() < 1e-3);
+    assert_ne!(values[1], 0.123456789);
+}
+
+#[test]
+#[cfg(feature = "4.9.0")]
+fn filter_available() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("filter_available.nc");
+    let mut file = netcdf::create(&path).unwrap();
+
+    assert!(file.filter_available(1).unwrap());
+    assert!(!file.filter_available(65535).unwrap());
+
+    let zstd = netcdf::Filter::zstd(3);
+    if file.filter_available(zstd.id).unwrap() {
+        file.add_dimension("x", 4).unwrap();
+        let mut var = file.add_variable::<f64>("zstd", &["x"]).unwrap();
+        var.add_filter(&zstd).unwrap();
+        var.put_values(&[1.0, 2.0, 3.0, 4.0], None, None).unwrap();
+        assert!(var.filters().unwrap().contains(&zstd));
+    }
+}
+
 #[test]
 fn set_compression_all_variables_in_a_group() {
     let d = tempfile::tempdir().expect("Could not create tempdir");
@@ -939,6 +1655,38 @@ fn read_from_memory() {
     }
 }
 
+#[test]
+#[cfg(feature = "memory")]
+fn write_to_memory() {
+    let bytes = {
+        let mut file = netcdf::create_mem(None).unwrap();
+        file.add_dimension("x", 6).unwrap();
+        file.add_attribute("title", "in memory").unwrap();
+        let var = &mut file.add_variable::<i32>("data", &["x"]).unwrap();
+        var.put_values(&[0, 1, 2, 3, 4, 5], None, None).unwrap();
+        file.close().unwrap()
+    };
+    assert!(!bytes.is_empty());
+
+    let file = netcdf::open_mem(None, &bytes).unwrap();
+    assert_eq!(file.dimension("x").unwrap().len(), 6);
+    assert_eq!(
+        file.attribute("title").unwrap().value().unwrap(),
+        netcdf::AttrValue::Str("in memory".into())
+    );
+    let mut v = vec![0i32; 6];
+    file.variable("data")
+        .unwrap()
+        .values_to(&mut v, None, None)
+        .unwrap();
+    assert_eq!(v, [0, 1, 2, 3, 4, 5]);
+
+    assert!(matches!(
+        netcdf::create_mem(Some("in\0memory")),
+        Err(netcdf::error::Error::NulError(_))
+    ));
+}
+
 #[test]
 fn add_conflicting_dimensions() {
     let d = tempfile::tempdir().unwrap();
@@ -1175,6 +1923,47 @@ fn string_variables() {
     var.string_value(None).unwrap_err();
 }
 
+#[test]
+fn string_values() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("string_values.nc");
+    {
+        let mut file = netcdf::create(&path).unwrap();
+
+        file.add_unlimited_dimension("x").unwrap();
+        file.add_dimension("y", 2).unwrap();
+
+        let var = &mut file.add_string_variable("str", &["x", "y"]).unwrap();
+
+        var.put_strings(&["a", "bb", "", "dddd"], None, Some(&[2, 2]))
+            .unwrap();
+        var.put_strings(&[String::from("ßæøå")], Some(&[3, 1]), Some(&[1, 1]))
+            .unwrap();
+        var.put_strings(&["nul\0"], Some(&[0, 0]), Some(&[1, 1]))
+            .unwrap_err();
+    }
+    let file = netcdf::open(path).unwrap();
+
+    let var = &file.variable("str").unwrap();
+
+    assert_eq!(
+        var.string_values(None, None).unwrap(),
+        &["a", "bb", "", "dddd", "", "", "", "ßæøå"]
+    );
+    assert_eq!(
+        var.string_values(Some(&[1, 1]), Some(&[1, 1])).unwrap(),
+        &["dddd"]
+    );
+    #[cfg(feature = "ndarray")]
+    {
+        let values = var
+            .string_values_array(Some(&[0, 1]), Some(&[4, 1]))
+            .unwrap();
+        assert_eq!(values.shape(), &[4, 1]);
+        assert_eq!(values[[3, 0]], "ßæøå");
+    }
+}
+
 #[test]
 fn unlimited_in_parents() {
     let d = tempfile::tempdir().expect("Could not create tempdir");
@@ -93,6 +93,42 @@ fn add_opaque() {
     // let var = file.typ("opa").unwrap();
 }
 
+#[test]
+fn put_get_opaque() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("test_put_get_opaque.nc");
+
+    {
+        let mut file = netcdf::create(&path).unwrap();
+        let typ = file.add_opaque_type("opa", 3).unwrap();
+        file.add_dimension("x", 4).unwrap();
+        file.add_variable::<u8>("bytes", &["x"]).unwrap();
+
+        let mut var = file
+            .add_variable_with_type("var", &["x"], &typ.into())
+            .unwrap();
+        for i in 0..4 {
+            var.put_opaque(&[i, i + 1, i + 2], &[usize::from(i)])
+                .unwrap();
+        }
+        assert!(var.put_opaque(&[0, 1], &[0]).is_err());
+        assert!(var.put_opaque(&[0, 1, 2, 3], &[0]).is_err());
+
+        let mut var = file.variable_mut("bytes").unwrap();
+        assert!(var.put_opaque(&[0], &[0]).is_err());
+    }
+
+    let file = netcdf::open(&path).unwrap();
+    let var = file.variable("var").unwrap();
+    let values = var.opaque_values(None, None).unwrap();
+    assert_eq!(values, &[[0, 1, 2], [1, 2, 3], [2, 3, 4], [3, 4, 5]]);
+    let values = var.opaque_values(Some(&[2]), Some(&[1])).unwrap();
+    assert_eq!(values, &[[2, 3, 4]]);
+
+    let var = file.variable("bytes").unwrap();
+    assert!(var.opaque_values(None, None).is_err());
+}
+
 #[test]
 fn add_vlen() {
     let d = tempfile::tempdir().unwrap();
@@ -234,6 +270,78 @@ fn read_compound_simple_nc4() {
     }
 }
 
+#[cfg(feature = "derive")]
+#[test]
+fn derive_compound() {
+    #[derive(Copy, Clone, Debug, PartialEq, netcdf::NcCompound)]
+    #[repr(C)]
+    struct Inner {
+        a: u8,
+        b: [[i16; 3]; 2],
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq, netcdf::NcCompound)]
+    #[repr(C)]
+    #[netcdf(rename = "outer")]
+    struct Outer {
+        x: f64,
+        #[netcdf(rename = "inner")]
+        i: Inner,
+        y: i8,
+    }
+
+    #[derive(Copy, Clone, netcdf::NcCompound)]
+    #[repr(C)]
+    struct Other {
+        x: f64,
+        y: i8,
+    }
+
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("test_derive_compound.nc");
+
+    let values = (0..4)
+        .map(|i| Outer {
+            x: f64::from(i) / 2.0,
+            i: Inner {
+                a: i as u8,
+                b: [[i as i16, 1, 2], [3, 4, -(i as i16)]],
+            },
+            y: -(i as i8),
+        })
+        .collect::<Vec<_>>();
+
+    {
+        let mut file = netcdf::create(&path).unwrap();
+        let outer = file.add_compound_type_from::<Outer>().unwrap();
+        assert_eq!(outer.name(), "outer");
+        let fields = outer.fields().collect::<Vec<_>>();
+        assert_eq!(fields.len(), 3);
+        assert_eq!(fields[1].name(), "inner");
+        assert_eq!(fields[2].offset(), std::mem::size_of::<Outer>() - 8);
+
+        file.add_dimension("x", 4).unwrap();
+        let mut var = file
+            .add_variable_with_type("var", &["x"], &outer.into())
+            .unwrap();
+        var.put_compound_values(&values, None, None).unwrap();
+        assert!(var
+            .put_compound_values(&values[..1], Some(&[0]), Some(&[2]))
+            .is_err());
+    }
+
+    let file = netcdf::open(&path).unwrap();
+    let var = file.variable("var").unwrap();
+    assert_eq!(var.compound_values::<Outer>(None, None).unwrap(), values);
+    assert_eq!(
+        var.compound_values::<Outer>(Some(&[2]), Some(&[2]))
+            .unwrap(),
+        &values[2..]
+    );
+    assert!(var.compound_values::<Other>(None, None).is_err());
+    assert!(var.compound_values::<Inner>(None, None).is_err());
+}
+
 #[test]
 fn put_get_enum() {
     let d = tempfile::tempdir().unwrap();
@@ -266,6 +374,65 @@ fn put_get_enum() {
     assert_eq!(bytes, bytes_copy);
 }
 
+#[test]
+fn put_get_enum_names() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("test_put_get_enum_names.nc");
+
+    {
+        let mut file = netcdf::create(&path).unwrap();
+        let e = file
+            .add_enum_type("e", &[("neg", -5_i8), ("zero", 0), ("pos", 7)])
+            .unwrap();
+        let u = file
+            .add_enum_type("u", &[("small", 1_u64), ("large", u64::MAX)])
+            .unwrap();
+        file.add_dimension("x", 4).unwrap();
+        file.add_variable::<i32>("plain", &["x"]).unwrap();
+
+        let mut var = file
+            .add_variable_with_type("var", &["x"], &e.into())
+            .unwrap();
+        var.put_enum_names(&["pos", "neg", "zero", "neg"], None, None)
+            .unwrap();
+        assert!(var
+            .put_enum_names(&["missing"], Some(&[0]), Some(&[1]))
+            .is_err());
+
+        let mut var = file
+            .add_variable_with_type("uvar", &["x"], &u.into())
+            .unwrap();
+        var.put_enum_names(&["large", "small"], Some(&[1]), Some(&[2]))
+            .unwrap();
+        var.put_enum_names(&["small", "large"], Some(&[0]), Some(&[1]))
+            .unwrap_err();
+        var.put_enum_names(&["small"], Some(&[0]), Some(&[1]))
+            .unwrap();
+        var.put_enum_names(&["large"], Some(&[3]), Some(&[1]))
+            .unwrap();
+    }
+
+    let file = netcdf::open(&path).unwrap();
+    let var = file.variable("var").unwrap();
+    assert_eq!(
+        var.enum_values(None, None).unwrap(),
+        &["pos", "neg", "zero", "neg"]
+    );
+    assert_eq!(
+        var.enum_values(Some(&[1]), Some(&[2])).unwrap(),
+        &["neg", "zero"]
+    );
+
+    let var = file.variable("uvar").unwrap();
+    assert_eq!(
+        var.enum_values(None, None).unwrap(),
+        &["small", "large", "small", "large"]
+    );
+
+    let var = file.variable("plain").unwrap();
+    assert!(var.enum_values(None, None).is_err());
+}
+
 #[test]
 fn put_get_vlen() {
     let d = tempfile::tempdir().unwrap();
@@ -297,6 +464,51 @@ fn put_get_vlen() {
     }
 }
 
+#[test]
+fn put_get_vlens() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("test_put_get_vlens.nc");
+
+    let buf = (0..12).collect::<Vec<i32>>();
+    {
+        let mut file = netcdf::create(&path).unwrap();
+        file.add_dimension("x", 3).unwrap();
+        file.add_unlimited_dimension("t").unwrap();
+        let v = file.add_vlen_type::<i32>("v").unwrap();
+
+        let mut var = file
+            .add_variable_with_type("var", &["t", "x"], &v.into())
+            .unwrap();
+
+        let values = (0..6).map(|i| &buf[i * 2..]).collect::<Vec<_>>();
+        var.put_vlens(&values, Some(&[0, 0]), Some(&[2, 3]))
+            .unwrap();
+        var.put_vlens::<i32, _>(&[vec![], vec![-1], vec![-2, -3]], Some(&[2, 0]), None)
+            .unwrap();
+        assert!(var.put_vlens(&[vec![1_i64]], Some(&[0, 0]), None).is_err());
+    }
+
+    let file = netcdf::open(&path).unwrap();
+    let var = file.variable("var").unwrap();
+
+    let values = var.vlen_values::<i32>(None, None).unwrap();
+    assert_eq!(values.len(), 9);
+    for (i, v) in values.iter().take(6).enumerate() {
+        assert_eq!(v, &buf[i * 2..]);
+    }
+    assert_eq!(values[6], &[]);
+    assert_eq!(values[7], &[-1]);
+    assert_eq!(values[8], &[-2, -3]);
+
+    let values = var
+        .vlen_values::<i32>(Some(&[1, 1]), Some(&[2, 2]))
+        .unwrap();
+    assert_eq!(values[0], &buf[8..]);
+    assert_eq!(values[3], &[-2, -3]);
+    assert_eq!(var.vlen::<i32>(&[2, 1]).unwrap(), &[-1]);
+    assert!(var.vlen_values::<u32>(None, None).is_err());
+}
+
 #[test]
 fn char() {
     use netcdf::types::BasicType;
@@ -320,3 +532,58 @@ fn char() {
     var.raw_values(&mut retrieved_vals, &[0], &[2]).unwrap();
     assert_eq!(vals, retrieved_vals);
 }
+
+#[test]
+fn char_strings() {
+    use netcdf::types::BasicType;
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("test_char_strings.nc");
+
+    {
+        let mut f = netcdf::create_with(&path, netcdf::Options::CLASSIC).unwrap();
+        f.add_dimension("n", 3).unwrap();
+        f.add_dimension("strlen", 5).unwrap();
+
+        f.add_variable_with_type("names", &["n", "strlen"], &BasicType::Char.into())
+            .unwrap();
+        let mut var = f
+            .add_variable_with_type("latin", &["n", "strlen"], &BasicType::Char.into())
+            .unwrap();
+        var.add_attribute("_Encoding", "ISO-8859-1").unwrap();
+        f.add_variable::<u8>("bytes", &["n", "strlen"]).unwrap();
+        // Leave define mode for classic files
+        f.define(|_| Ok(())).unwrap();
+
+        let mut var = f.variable_mut("names").unwrap();
+        var.put_char_strings(&["ab  ", "hello", "x"], None, None)
+            .unwrap();
+        assert!(var
+            .put_char_strings(&["toolong"], Some(&[0]), Some(&[1]))
+            .is_err());
+
+        let mut var = f.variable_mut("latin").unwrap();
+        var.put_char_strings(&["ßæøå"], Some(&[1]), Some(&[1]))
+            .unwrap();
+        assert!(var
+            .put_char_strings(&["€"], Some(&[0]), Some(&[1]))
+            .is_err());
+    }
+
+    let f = netcdf::open(&path).unwrap();
+    let var = f.variable("names").unwrap();
+    assert_eq!(var.char_strings(None, None).unwrap(), &["ab", "hello", "x"]);
+    assert_eq!(
+        var.char_strings(Some(&[1]), Some(&[2])).unwrap(),
+        &["hello", "x"]
+    );
+    assert!(var.char_strings(Some(&[1, 0]), None).is_err());
+
+    let var = f.variable("latin").unwrap();
+    assert_eq!(var.char_strings(Some(&[1]), Some(&[1])).unwrap(), &["ßæøå"]);
+    let mut raw = [0_u8; 5];
+    var.raw_values(&mut raw, &[1, 0], &[1, 5]).unwrap();
+    assert_eq!(raw, [0xdf, 0xe6, 0xf8, 0xe5, 0]);
+
+    let var = f.variable("bytes").unwrap();
+    assert!(var.char_strings(None, None).is_err());
+}